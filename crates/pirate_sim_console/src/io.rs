@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
 use bevy_input::prelude::*;
@@ -7,12 +9,21 @@ use bevy_reflect::Reflect;
 
 use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
 
-use super::parse;
+use super::{parse, registration::RegisterConsoleCommand, Token};
 
 /// A resource tracking whether or not the console is currently open
 #[derive(Deref, DerefMut, Reflect, Resource)]
 pub struct IsOpen(pub bool);
 
+/// A run condition for systems that should pause while the console is open, eg. anything reading
+/// gameplay input.
+///
+/// If the console hasn't been set up, it is treated as closed.
+#[must_use]
+pub fn console_closed(is_open: Option<Res<IsOpen>>) -> bool {
+    !is_open.is_some_and(|is_open| **is_open)
+}
+
 // /// The resource for console commands to write their output to
 // #[derive(Deref, DerefMut, Resource, Reflect)]
 // pub struct CommandOutput(pub Sender<String>);
@@ -28,6 +39,27 @@ pub enum Output {
 
 const MAX_HISTORY_SIZE: usize = 500;
 
+/// Everything that has been written to the console so far
+#[derive(Deref, DerefMut, Resource, Default, Debug)]
+pub struct OutputHistory(pub String);
+
+/// A command to empty the [`OutputHistory`]
+pub struct ClearHistoryCommand;
+
+impl bevy_ecs::system::Command for ClearHistoryCommand {
+    fn apply(self, world: &mut World) {
+        if let Some(mut output_history) = world.get_resource_mut::<OutputHistory>() {
+            output_history.clear();
+        }
+
+        world.send_event(Output::End);
+    }
+}
+
+fn clear_command(_input: VecDeque<Token>, commands: &mut Commands) {
+    commands.add(ClearHistoryCommand);
+}
+
 /// a system to open the console when backtick is pressed
 fn check_open_console(keys: Res<Input<KeyCode>>, mut showing_console: ResMut<IsOpen>) {
     if keys.just_pressed(KeyCode::Grave) {
@@ -39,7 +71,7 @@ fn check_open_console(keys: Res<Input<KeyCode>>, mut showing_console: ResMut<IsO
 /// behemoth system to Handle drawing the console and taking input
 fn do_io(
     mut input: Local<String>,
-    mut output_history: Local<String>,
+    mut output_history: ResMut<OutputHistory>,
     mut waiting_for_command: Local<bool>,
     mut context: EguiContexts,
     mut showing_console: ResMut<IsOpen>,
@@ -51,7 +83,8 @@ fn do_io(
         return;
     }
 
-    let original_output_history = output_history.clone();
+    let original_output_history = output_history.0.clone();
+    let mut clear_requested = false;
 
     let mut write_output = |string: &str| {
         if output_history.len() + string.len() > MAX_HISTORY_SIZE {
@@ -129,14 +162,25 @@ fn do_io(
                         if i.key_pressed(egui::Key::Escape) {
                             showing_console.0 = false;
                         }
+
+                        // same as the clear command
+                        if i.modifiers.ctrl && i.key_pressed(egui::Key::L) {
+                            clear_requested = true;
+                        }
                     });
                 })
             });
         });
+
+    if clear_requested {
+        output_history.clear();
+    }
 }
 
 fn startup(mut commands: Commands, type_registry: Res<AppTypeRegistry>) {
     commands.insert_resource(IsOpen(false));
+    commands.init_resource::<OutputHistory>();
+    commands.add(RegisterConsoleCommand::new("clear".into(), clear_command));
 
     let mut w = type_registry.write();
     w.add_registration(IsOpen::get_type_registration());
@@ -21,20 +21,32 @@
 mod io;
 pub mod registration;
 
-use std::collections::VecDeque;
+#[cfg(test)]
+mod test;
 
+use std::{collections::VecDeque, ops::Range};
+
+use bevy_core::Name;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
 use bevy_log::prelude::*;
 use bevy_utils::HashMap;
 use thiserror::Error;
 
+pub use io::console_closed;
+pub use io::ClearHistoryCommand;
 pub use io::IsOpen;
 pub use io::Output;
+pub use io::OutputHistory;
 
+/// A single argument of a console command, as split up by [`parse`]
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Token {
+    /// The argument with its quotes and escapes already handled
     pub string: String,
+    /// The byte range this token was parsed from in the original input, including any quotes and
+    /// backslashes, eg. for pointing at it in an error message
+    pub span: Range<usize>,
 }
 
 #[derive(Error, Debug)]
@@ -50,13 +62,19 @@ pub enum ParseError {
 /// Parse commandline input. Currently just splits up strings with backslash and quote escaping
 ///
 /// This needs to be moved either to io.rs or to io/parse.rs
-fn parse(to_parse: &str) -> Result<VecDeque<Token>, ParseError> {
+///
+/// # Errors
+/// If a backslash escapes anything but a space, backslash, or quote, or the input ends while still
+/// escaped or quoted. See [`ParseError`]
+pub fn parse(to_parse: &str) -> Result<VecDeque<Token>, ParseError> {
     trace!("parsing string `{}`", to_parse);
 
     let mut tokens: VecDeque<Token> = VecDeque::new();
     let mut cur_string = String::new();
     let mut is_backslash_escaped = false;
     let mut is_in_quotes = false;
+    // where the token being built starts, once anything has been typed for it
+    let mut token_start = None;
 
     trace!("c - \"     \\");
     for (i, char) in to_parse.char_indices() {
@@ -77,11 +95,18 @@ fn parse(to_parse: &str) -> Result<VecDeque<Token>, ParseError> {
                 c => cur_string.push(c),
             }
         } else {
+            if char != ' ' {
+                token_start.get_or_insert(i);
+            }
+
             match char {
                 '\\' => is_backslash_escaped = true,
                 '"' => is_in_quotes = true,
                 ' ' => {
-                    tokens.push_back(Token { string: cur_string });
+                    tokens.push_back(Token {
+                        string: cur_string,
+                        span: token_start.take().unwrap_or(i)..i,
+                    });
                     cur_string = String::new();
                 }
                 c => cur_string.push(c),
@@ -94,7 +119,10 @@ fn parse(to_parse: &str) -> Result<VecDeque<Token>, ParseError> {
     } else if is_in_quotes {
         return Err(ParseError::EndQuoted());
     } else if !cur_string.is_empty() {
-        tokens.push_back(Token { string: cur_string });
+        tokens.push_back(Token {
+            string: cur_string,
+            span: token_start.unwrap_or(to_parse.len())..to_parse.len(),
+        });
     }
 
     trace!("{:?}", tokens);
@@ -114,7 +142,8 @@ pub(self) struct RegisteredConsoleCommands(HashMap<Box<str>, CommandObject>);
 pub struct Plugin;
 impl bevy_app::Plugin for Plugin {
     fn build(&self, app: &mut bevy_app::App) {
-        app.add_plugins(self::io::Plugin);
+        app.add_plugins(self::io::Plugin)
+            .init_resource::<SelectedEntity>();
     }
 }
 
@@ -128,3 +157,94 @@ impl bevy_ecs::system::Command for PrintStringCommand {
         world.send_event(Output::End);
     }
 }
+
+/// Call `apply` on every entity whose [`Name`] matches `predicate`, returning how many entities
+/// were affected.
+///
+/// Matching entities are collected before any are applied to, so `apply` is free to modify the
+/// world however it likes.
+pub fn apply_to_named(
+    world: &mut World,
+    predicate: impl Fn(&Name) -> bool,
+    mut apply: impl FnMut(Entity, &mut World),
+) -> usize {
+    let matching: Vec<_> = world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .filter_map(|(entity, name)| predicate(name).then_some(entity))
+        .collect();
+
+    for entity in &matching {
+        apply(*entity, world);
+    }
+
+    matching.len()
+}
+
+/// The token commands accept in place of a name to refer to the [`SelectedEntity`]
+pub const SELECTED_TOKEN: &str = "@selected";
+
+/// The entity picked in game for console commands to act on. See [`SELECTED_TOKEN`]
+#[derive(Resource, Debug, Default, Clone, Copy, Deref, DerefMut)]
+pub struct SelectedEntity(pub Option<Entity>);
+
+/// The selected entity, if there is one and it still exists
+fn selected(world: &World) -> Option<Entity> {
+    world
+        .get_resource::<SelectedEntity>()
+        .and_then(|selected| selected.0)
+        .filter(|entity| world.get_entity(*entity).is_some())
+}
+
+/// Find the entity a command's `target` refers to. This is either the [`SelectedEntity`] for
+/// [`SELECTED_TOKEN`], or the first entity named `target`.
+pub fn find_target(world: &mut World, target: &str) -> Option<Entity> {
+    if target == SELECTED_TOKEN {
+        return selected(world);
+    }
+
+    world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .find_map(|(entity, name)| (name.as_str() == target).then_some(entity))
+}
+
+/// [`apply_to_named`] on every entity named `target`, or on the [`SelectedEntity`] for
+/// [`SELECTED_TOKEN`].
+pub fn apply_to_target(
+    world: &mut World,
+    target: &str,
+    mut apply: impl FnMut(Entity, &mut World),
+) -> usize {
+    if target == SELECTED_TOKEN {
+        return match selected(world) {
+            Some(entity) => {
+                apply(entity, world);
+                1
+            }
+            None => 0,
+        };
+    }
+
+    apply_to_named(world, |name| name.as_str() == target, apply)
+}
+
+/// A command to run [`apply_to_named`] and print how many entities were affected, for console
+/// commands that operate on many entities at once.
+pub struct ApplyToNamedCommand<Predicate, Apply> {
+    pub predicate: Predicate,
+    pub apply: Apply,
+}
+
+impl<Predicate, Apply> bevy_ecs::system::Command for ApplyToNamedCommand<Predicate, Apply>
+where
+    Predicate: Fn(&Name) -> bool + Send + 'static,
+    Apply: FnMut(Entity, &mut World) + Send + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let affected = apply_to_named(world, self.predicate, self.apply);
+
+        world.send_event(Output::String(format!("Affected {affected} entities")));
+        world.send_event(Output::End);
+    }
+}
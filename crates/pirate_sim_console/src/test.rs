@@ -0,0 +1,138 @@
+//! Tests for the console
+
+use bevy_app::prelude::*;
+use bevy_core::Name;
+use bevy_ecs::{event::Events, prelude::*, system::Command};
+
+use crate::{
+    apply_to_target, console_closed, parse, ApplyToNamedCommand, ClearHistoryCommand, IsOpen,
+    Output, OutputHistory, ParseError, SelectedEntity, SELECTED_TOKEN,
+};
+
+#[derive(Component)]
+struct Marked;
+
+#[test]
+fn apply_to_named_reports_count() {
+    let mut world = World::new();
+    world.init_resource::<Events<Output>>();
+
+    for name in ["Crab 1", "Crab 2", "Crab 3", "Gull"] {
+        world.spawn(Name::new(name));
+    }
+
+    ApplyToNamedCommand {
+        predicate: |name: &Name| name.starts_with("Crab"),
+        apply: |entity, world: &mut World| {
+            world.entity_mut(entity).insert(Marked);
+        },
+    }
+    .apply(&mut world);
+
+    let marked: Vec<_> = world
+        .query_filtered::<&Name, With<Marked>>()
+        .iter(&world)
+        .map(Name::as_str)
+        .collect();
+
+    assert_eq!(marked.len(), 3);
+    assert!(!marked.contains(&"Gull"));
+
+    let output: Vec<_> = world
+        .resource_mut::<Events<Output>>()
+        .drain()
+        .filter_map(|o| match o {
+            Output::String(s) => Some(s),
+            Output::End => None,
+        })
+        .collect();
+
+    assert_eq!(output, vec!["Affected 3 entities".to_owned()]);
+}
+
+#[test]
+fn console_closed_pauses_systems() {
+    #[derive(Resource, Default)]
+    struct Runs(u32);
+
+    let mut app = App::new();
+
+    app.init_resource::<Runs>().add_systems(
+        Update,
+        (|mut runs: ResMut<Runs>| runs.0 += 1).run_if(console_closed),
+    );
+
+    // no console at all counts as closed
+    app.update();
+    assert_eq!(app.world.resource::<Runs>().0, 1);
+
+    app.insert_resource(IsOpen(true));
+    app.update();
+    assert_eq!(app.world.resource::<Runs>().0, 1);
+
+    app.insert_resource(IsOpen(false));
+    app.update();
+    assert_eq!(app.world.resource::<Runs>().0, 2);
+}
+
+#[test]
+fn selected_token_targets_selection() {
+    let mut world = World::new();
+
+    let crab = world.spawn(Name::new("Crab")).id();
+    world.spawn(Name::new("Gull"));
+
+    // nothing is selected yet
+    assert_eq!(apply_to_target(&mut world, SELECTED_TOKEN, |_, _| {}), 0);
+
+    world.insert_resource(SelectedEntity(Some(crab)));
+
+    let mut applied = Vec::new();
+    let affected = apply_to_target(&mut world, SELECTED_TOKEN, |entity, _| applied.push(entity));
+
+    assert_eq!(affected, 1);
+    assert_eq!(applied, vec![crab]);
+
+    // a despawned selection shouldn't resolve
+    world.despawn(crab);
+    assert_eq!(apply_to_target(&mut world, SELECTED_TOKEN, |_, _| {}), 0);
+}
+
+#[test]
+fn clear_empties_history() {
+    let mut world = World::new();
+    world.init_resource::<Events<Output>>();
+    world.insert_resource(OutputHistory("\nhello\nworld".to_owned()));
+
+    ClearHistoryCommand.apply(&mut world);
+
+    assert!(world.resource::<OutputHistory>().is_empty());
+
+    // the console should stop waiting on the command
+    let output: Vec<_> = world.resource_mut::<Events<Output>>().drain().collect();
+    assert!(matches!(output[..], [Output::End]));
+}
+
+#[test]
+fn token_spans_map_back_to_the_input() {
+    let input = r#"echo "hello world"  a\ b"#;
+    let tokens = parse(input).expect("input parses");
+
+    let strings: Vec<_> = tokens.iter().map(|t| t.string.as_str()).collect();
+    assert_eq!(strings, vec!["echo", "hello world", "", "a b"]);
+
+    let spanned: Vec<_> = tokens.iter().map(|t| &input[t.span.clone()]).collect();
+    // quotes and escapes are part of the span, and the extra space leaves an empty token
+    assert_eq!(spanned, vec!["echo", r#""hello world""#, "", r"a\ b"]);
+
+    // tokens in quotes don't have to be the whole of the token
+    let input = r#"say x"y z"w"#;
+    let tokens = parse(input).expect("input parses");
+    assert_eq!(tokens[1].string, "xy zw");
+    assert_eq!(&input[tokens[1].span.clone()], r#"x"y z"w"#);
+
+    assert!(matches!(
+        parse(r#"say "oops"#),
+        Err(ParseError::EndQuoted())
+    ));
+}
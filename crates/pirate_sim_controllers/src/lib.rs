@@ -15,9 +15,9 @@
 
 use std::time::Duration;
 
-use bevy_app::{App, PostUpdate, Startup, Update};
+use bevy_app::{App, Last, PostUpdate, Startup, Update};
 use bevy_derive::{Deref, DerefMut};
-use bevy_ecs::prelude::*;
+use bevy_ecs::{prelude::*, system::EntityCommand};
 use bevy_log::warn;
 use bevy_math::prelude::*;
 use bevy_reflect::{prelude::*, GetTypeRegistration};
@@ -25,15 +25,42 @@ use bevy_time::{Time, Timer};
 
 use pirate_sim_core::goals::MovementGoal;
 use pirate_sim_core::PhysicsSet;
+use pirate_sim_physics::movement::Facing;
 
+pub mod npc;
 pub mod player;
 
+#[cfg(test)]
+mod test;
+
 // make diagonals a little slower so they're less desireable
 const DIAG_SPEED: f32 = 1. / 1.5;
 
+/// How fast an entity walks, in tiles per second.
+///
+/// A [`MovementGoal`] is a velocity in tiles per second, so walking with a goal from
+/// [`WalkSpeed::goal_towards`] moves an entity `WalkSpeed.0` tiles every second, no matter the
+/// frame rate.
 #[derive(Component, Default, Reflect, Deref, DerefMut)]
 pub struct WalkSpeed(pub f32);
 
+impl WalkSpeed {
+    /// The movement goal for walking along `direction` at this speed. `direction` should have a
+    /// length of 1 for the goal to be exactly this speed.
+    #[must_use]
+    #[inline]
+    pub fn goal_towards(&self, direction: Vec3) -> MovementGoal {
+        MovementGoal(self.0 * direction)
+    }
+
+    /// How long it takes to walk a single tile at this speed, in seconds
+    #[must_use]
+    #[inline]
+    pub fn seconds_per_tile(&self) -> f32 {
+        1. / self.0
+    }
+}
+
 #[derive(Component, Debug, Default, Reflect, Deref)]
 pub(self) struct MovementGoalTimer(Timer);
 
@@ -43,6 +70,47 @@ impl MovementGoalTimer {
     }
 }
 
+/// Set an entity's [`MovementGoal`], how long until it times out, and which way it faces, all at
+/// once, so that nothing sees one changed without the others.
+///
+/// Without a `timeout` the goal lasts until it is changed, and without a `facing` the entity
+/// keeps facing whichever way it was. Use with [`EntityCommands::add`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SetMovement {
+    pub goal: Vec3,
+    /// Seconds until the goal is reset to zero
+    pub timeout: Option<f32>,
+    pub facing: Option<IVec3>,
+}
+
+impl EntityCommand for SetMovement {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            warn!("Tried to set the movement of {id:?}, which doesn't exist");
+            return;
+        };
+
+        entity.insert(MovementGoal(self.goal));
+
+        match self.timeout {
+            Some(timeout) => {
+                entity.insert(MovementGoalTimer::new(timeout));
+            }
+            // an already finished timer would reset the new goal straight away
+            None => {
+                if let Some(mut timer) = entity.get_mut::<MovementGoalTimer>() {
+                    timer.0.reset();
+                    timer.0.pause();
+                }
+            }
+        }
+
+        if let Some(facing) = self.facing {
+            entity.insert(Facing(facing));
+        }
+    }
+}
+
 /// A system to timeout movement goals based on their timeout component.
 ///
 /// Should run after physics updates
@@ -68,16 +136,29 @@ fn register_types(type_registry: Res<AppTypeRegistry>) {
 
     type_registry_w.add_registration(self::MovementGoalTimer::get_type_registration());
     type_registry_w.add_registration(self::WalkSpeed::get_type_registration());
+    type_registry_w.add_registration(npc::Wander::get_type_registration());
+    type_registry_w.add_registration(player::CameraFollowConfig::get_type_registration());
 }
 
 pub struct Plugin;
 impl bevy_app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
+        let update_movement_goal = player::update_movement_goal.in_set(PhysicsSet::Input);
+        // the console takes over the keyboard while it's open
+        #[cfg(feature = "developer-tools")]
+        let update_movement_goal = update_movement_goal.run_if(pirate_sim_console::console_closed);
+
         app.add_systems(Startup, register_types)
             .add_systems(PostUpdate, count_down_goals)
+            .add_systems(Update, update_movement_goal)
+            .add_systems(Update, npc::wander.in_set(PhysicsSet::Input))
             .add_systems(
                 Update,
-                player::update_movement_goal.in_set(PhysicsSet::Input),
-            );
+                player::camera_follow_player.after(PhysicsSet::Completed),
+            )
+            // tile changes are sent at the end of PostUpdate
+            .add_systems(Last, player::send_player_moved)
+            .add_event::<player::PlayerMoved>()
+            .init_resource::<player::CameraFollowConfig>();
     }
 }
@@ -0,0 +1,89 @@
+//! Controllers for non-player characters
+
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::Time;
+use bevy_transform::prelude::*;
+
+use pirate_sim_core::{
+    goals::MovementGoal,
+    random::Generator,
+    tile_grid::{GetTileLocation, TileRegion, TileStretch},
+};
+
+use crate::WalkSpeed;
+
+/// The directions a wandering entity picks between, including standing still
+const WANDER_DIRECTIONS: [IVec3; 5] = [IVec3::ZERO, IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y];
+
+/// Wander around at random inside of `region`, picking a new direction to walk in, or standing
+/// still, every `change_interval` seconds. Walks at the entity's [`WalkSpeed`].
+///
+/// A direction that would step out of the region is never walked in, so a wandering entity won't
+/// leave it on its own, and one that starts outside of it stands still. Directions are drawn from
+/// the shared [`Generator`], so wandering is reproducible from its seed.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Wander {
+    pub region: TileRegion,
+    /// How many seconds to walk in a direction before picking a new one
+    pub change_interval: f32,
+    direction: IVec3,
+    until_change: f32,
+}
+
+impl Wander {
+    #[must_use]
+    pub fn new(region: TileRegion, change_interval: f32) -> Self {
+        Self {
+            region,
+            change_interval,
+            direction: IVec3::ZERO,
+            until_change: 0.,
+        }
+    }
+
+    /// The direction currently being walked in
+    #[must_use]
+    pub fn direction(&self) -> IVec3 {
+        self.direction
+    }
+}
+
+pub(crate) fn wander(
+    mut wander_q: Query<(&mut Wander, &mut MovementGoal, &WalkSpeed, &GlobalTransform)>,
+    generator: Option<ResMut<Generator>>,
+    tile_stretch: Res<TileStretch>,
+    time: Res<Time>,
+) {
+    let Some(mut generator) = generator else {
+        return;
+    };
+
+    for (mut wander, mut goal, walk_speed, transform) in &mut wander_q {
+        let location = transform.location(*tile_stretch);
+        let region = wander.region;
+        let stays_inside = |direction: &IVec3| region.contains(location + *direction);
+
+        wander.until_change -= time.delta_seconds();
+
+        // turn before walking out of the region, not just when it's time to
+        if wander.until_change <= 0. || !stays_inside(&wander.direction) {
+            let choices: Vec<_> = WANDER_DIRECTIONS.into_iter().filter(stays_inside).collect();
+
+            wander.direction = if choices.is_empty() {
+                IVec3::ZERO
+            } else {
+                choices[generator.range(0, choices.len())]
+            };
+            wander.until_change = wander.change_interval;
+        }
+
+        let new_goal = walk_speed.goal_towards(wander.direction.as_vec3());
+
+        // avoid triggering change detection every frame
+        if goal.0 != new_goal.0 {
+            *goal = new_goal;
+        }
+    }
+}
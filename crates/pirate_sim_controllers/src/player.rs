@@ -2,18 +2,37 @@ use bevy_ecs::prelude::*;
 use bevy_input::prelude::*;
 use bevy_log::prelude::*;
 use bevy_math::prelude::*;
+use bevy_reflect::Reflect;
 use bevy_render::prelude::*;
+use bevy_time::Time;
 use bevy_transform::prelude::*;
 
 use crate::{MovementGoalTimer, DIAG_SPEED};
-#[cfg(feature = "developer-tools")]
-use pirate_sim_console as console;
-use pirate_sim_core::goals::MovementGoal;
+use pirate_sim_core::{goals::MovementGoal, tile_grid::TileChanged};
 
 /// A marker for an entity controlled as a player
 #[derive(Component, Default)]
 pub struct Controller();
 
+/// Sent whenever a player moves onto a new tile, eg. for minimaps. Filtered from
+/// [`TileChanged`] events down to entities with a [`Controller`]
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlayerMoved {
+    pub tile: IVec3,
+}
+
+pub(super) fn send_player_moved(
+    mut tile_changed: EventReader<TileChanged>,
+    mut player_moved: EventWriter<PlayerMoved>,
+    controller_q: Query<(), With<Controller>>,
+) {
+    for changed in &mut tile_changed {
+        if controller_q.contains(changed.entity) {
+            player_moved.send(PlayerMoved { tile: changed.to });
+        }
+    }
+}
+
 #[derive(Bundle, Default)]
 pub struct PlayerControllerBundle {
     movement_goal: MovementGoal,
@@ -21,21 +40,72 @@ pub struct PlayerControllerBundle {
     controler: Controller,
 }
 
-/// a system to make the player the center of the screen
-#[allow(unused)]
+/// How the camera follows the player. Copying the player's translation straight onto the camera
+/// jitters as the player snaps from tile to tile, so the camera glides after it instead.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct CameraFollowConfig {
+    /// The fraction of the distance to the player the camera is still behind by after following it
+    /// for a 60th of a second, between 0 and 1. 0 keeps the camera right on the player, and the
+    /// closer to 1 the slower it catches up.
+    pub smoothing: f32,
+}
+
+impl Default for CameraFollowConfig {
+    fn default() -> Self {
+        Self { smoothing: 0.8 }
+    }
+}
+
+/// A marker for a camera that follows the player, see [`CameraFollowConfig`]
+#[derive(Component, Default)]
+pub struct FollowPlayer();
+
+/// a system to keep the player in the center of each [`FollowPlayer`] camera, see
+/// [`CameraFollowConfig`]. Only x and y are followed, so the camera stays at its own z level.
 pub(super) fn camera_follow_player(
-    player: Query<(&Controller, &Transform), (Without<Camera>, Changed<Transform>)>,
-    mut cameras: Query<(&mut Transform, &Camera), Without<Controller>>,
+    player: Query<&Transform, (With<Controller>, Without<Camera>)>,
+    mut cameras: Query<&mut Transform, (With<Camera>, With<FollowPlayer>, Without<Controller>)>,
+    config: Res<CameraFollowConfig>,
+    time: Res<Time>,
 ) {
-    if let Ok(player) = player.get_single() {
-        // in the future with multi camera system this will need to iterate
-        let mut camera = cameras.get_single_mut().expect("Camera not found");
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    // scaled by the frame time, so that the camera glides the same at any frame rate
+    let behind = config
+        .smoothing
+        .clamp(0., 1.)
+        .powf(time.delta_seconds() * 60.);
+
+    for mut camera in &mut cameras {
+        let followed = player
+            .translation
+            .truncate()
+            .lerp(camera.translation.truncate(), behind);
+        camera.translation = followed.extend(camera.translation.z);
+    }
+}
 
-        camera.0.translation = player.1.translation;
+/// The direction a movement key walks in, if it is one
+fn key_direction(key: KeyCode) -> Option<Vec3> {
+    match key {
+        KeyCode::W => Some(Vec3::Y),
+        KeyCode::A => Some(Vec3::NEG_X),
+        KeyCode::X => Some(Vec3::NEG_Y),
+        KeyCode::D => Some(Vec3::X),
+        KeyCode::E => Some(Vec3::X + Vec3::Y),
+        KeyCode::Q => Some(Vec3::NEG_X + Vec3::Y),
+        KeyCode::Z => Some(Vec3::NEG_Y + Vec3::NEG_X),
+        KeyCode::C => Some(Vec3::NEG_Y + Vec3::X),
+        _ => None,
     }
 }
 
 /// Handle player inputs to do with movement goals.
+///
+/// Holding a movement key keeps renewing the goal and its timer, and releasing every movement key
+/// clears the goal straight away.
 pub(super) fn update_movement_goal(
     char_input_events: Res<Input<KeyCode>>,
     mut player: Query<
@@ -46,34 +116,26 @@ pub(super) fn update_movement_goal(
         ),
         With<Controller>,
     >,
-    #[cfg(feature = "developer-tools")] console_open: Res<console::IsOpen>,
 ) {
-    #[cfg(feature = "developer-tools")]
-    if **console_open {
-        return;
-    }
-
     let (mut movement_goal, mut movement_goal_timer, walk_speed) =
         player.get_single_mut().expect("Player not found");
 
-    // return if no movement was requested
     let Some(wanted_dir) = char_input_events
         .get_pressed()
-        .fold(None, |acc, event| {
-                
-            match event {
-                KeyCode::W => Some(Vec3::Y),
-                KeyCode::A => Some(Vec3::NEG_X),
-                KeyCode::X => Some(Vec3::NEG_Y),
-                KeyCode::D => Some(Vec3::X),
-                KeyCode::E => Some(Vec3::X + Vec3::Y),
-                KeyCode::Q => Some(Vec3::NEG_X + Vec3::Y),
-                KeyCode::Z => Some(Vec3::NEG_Y + Vec3::NEG_X),
-                KeyCode::C => Some(Vec3::NEG_Y + Vec3::X),
-                _ => None,
-            }.map(|dir| dir + acc.unwrap_or(Vec3::ZERO))
-            
-        }) else {return};
+        .filter_map(|key| key_direction(*key))
+        .reduce(|acc, dir| acc + dir)
+    else {
+        // stop as soon as the last movement key is let go, instead of drifting until the timer
+        // runs out
+        if char_input_events
+            .get_just_released()
+            .any(|key| key_direction(*key).is_some())
+        {
+            *movement_goal = MovementGoal(Vec3::ZERO);
+        }
+
+        return;
+    };
 
     let wanted_dir = wanted_dir.clamp(Vec3::NEG_ONE, Vec3::ONE);
 
@@ -86,6 +148,6 @@ pub(super) fn update_movement_goal(
         1.
     };
 
-    *movement_goal = MovementGoal(walk_speed.0 * diagonal_loss * wanted_dir);
-    *movement_goal_timer = MovementGoalTimer::new(1. / (walk_speed.0 * diagonal_loss));
+    *movement_goal = walk_speed.goal_towards(diagonal_loss * wanted_dir);
+    *movement_goal_timer = MovementGoalTimer::new(walk_speed.seconds_per_tile() / diagonal_loss);
 }
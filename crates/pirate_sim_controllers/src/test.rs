@@ -0,0 +1,479 @@
+#![allow(clippy::unwrap_used)]
+
+use bevy_app::prelude::*;
+use bevy_core::Name;
+use bevy_input::prelude::*;
+use bevy_math::prelude::*;
+use bevy_time::TimeUpdateStrategy;
+use bevy_transform::prelude::*;
+
+use pirate_sim_core::test_utils::DefaultTestPlugin;
+use pirate_sim_physics::{movement::MovementBundle, PhysicsPlugin};
+
+use crate::{
+    player::{PlayerControllerBundle, PlayerMoved},
+    WalkSpeed,
+};
+
+/// Walk along x for one second at `frame_rate`, returning the final translation
+fn walk_for_a_second(walk_speed: &WalkSpeed, frame_rate: u16) -> Vec3 {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins(PhysicsPlugin {
+        delta_override: Some(1. / f32::from(frame_rate)),
+        ..Default::default()
+    });
+
+    let walker = app
+        .world
+        .spawn((
+            Name::new("Walker"),
+            MovementBundle::default(),
+            walk_speed.goal_towards(Vec3::X),
+            TransformBundle::default(),
+        ))
+        .id();
+
+    for _ in 0..frame_rate {
+        app.update();
+    }
+
+    app.world.get::<Transform>(walker).unwrap().translation
+}
+
+#[test]
+fn walk_speed_is_tiles_per_second() {
+    let walk_speed = WalkSpeed(5.);
+
+    for frame_rate in [20, 60, 144] {
+        assert_eq!(
+            walk_for_a_second(&walk_speed, frame_rate),
+            Vec3::new(5., 0., 0.),
+            "walked the wrong distance at {frame_rate} fps"
+        );
+    }
+}
+
+#[test]
+#[allow(clippy::float_cmp)]
+fn releasing_keys_stops_immediately() {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins((
+        PhysicsPlugin {
+            delta_override: Some(1. / 60.),
+            ..Default::default()
+        },
+        crate::Plugin,
+    ));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+    app.init_resource::<Input<KeyCode>>();
+
+    let player = app
+        .world
+        .spawn((
+            Name::new("Player"),
+            PlayerControllerBundle::default(),
+            MovementBundle::default(),
+            WalkSpeed(5.),
+            TransformBundle::default(),
+        ))
+        .id();
+
+    // two and a half tiles worth of walking
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let released_at = app.world.get::<Transform>(player).unwrap().translation;
+    assert_eq!(released_at, Vec3::new(2., 0., 0.));
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::D);
+    app.update();
+    app.world.resource_mut::<Input<KeyCode>>().clear();
+
+    for _ in 0..30 {
+        app.update();
+    }
+
+    assert_eq!(
+        app.world.get::<Transform>(player).unwrap().translation,
+        released_at,
+        "player should stop in the tile it was in when the key was released"
+    );
+}
+
+/// Walk a player along x for a second towards a crate in the next tile, and a wall at `wall` if
+/// given, returning the final translations of the player and crate
+fn push_crate_for_a_second(wall: Option<Vec3>) -> (Vec3, Vec3) {
+    use pirate_sim_physics::Collider;
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins((
+        PhysicsPlugin {
+            delta_override: Some(1. / 60.),
+            ..Default::default()
+        },
+        crate::Plugin,
+    ));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+    app.init_resource::<Input<KeyCode>>();
+
+    let player = app
+        .world
+        .spawn((
+            Name::new("Player"),
+            PlayerControllerBundle::default(),
+            MovementBundle::default(),
+            Collider::entity(),
+            WalkSpeed(5.),
+            TransformBundle::default(),
+        ))
+        .id();
+
+    let pushed = app
+        .world
+        .spawn((
+            Name::new("Crate"),
+            MovementBundle::default(),
+            Collider::entity(),
+            TransformBundle::from_transform(Transform::from_xyz(1., 0., 0.)),
+        ))
+        .id();
+
+    if let Some(wall) = wall {
+        app.world.spawn((
+            Name::new("Wall"),
+            Collider::wall(),
+            TransformBundle::from_transform(Transform::from_translation(wall)),
+        ));
+    }
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..60 {
+        app.update();
+    }
+
+    (
+        app.world.get::<Transform>(player).unwrap().translation,
+        app.world.get::<Transform>(pushed).unwrap().translation,
+    )
+}
+
+#[test]
+fn player_pushes_crates() {
+    let (player, pushed) = push_crate_for_a_second(None);
+
+    // pushing doesn't slow the player down
+    assert_eq!(player, Vec3::new(5., 0., 0.));
+    assert_eq!(pushed, Vec3::new(6., 0., 0.));
+
+    // but a crate with something solid behind it can't be pushed
+    let (player, pushed) = push_crate_for_a_second(Some(Vec3::new(2., 0., 0.)));
+
+    assert_eq!(player, Vec3::ZERO);
+    assert_eq!(pushed, Vec3::new(1., 0., 0.));
+}
+
+#[test]
+fn player_moved_is_sent_per_tile() {
+    use bevy_ecs::event::{Events, ManualEventReader};
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins((
+        PhysicsPlugin {
+            delta_override: Some(1. / 60.),
+            ..Default::default()
+        },
+        crate::Plugin,
+    ));
+    app.init_resource::<Input<KeyCode>>();
+
+    let player = app
+        .world
+        .spawn((
+            Name::new("Player"),
+            PlayerControllerBundle::default(),
+            MovementBundle::default(),
+            WalkSpeed(5.),
+            TransformBundle::default(),
+        ))
+        .id();
+    // moves too, but isn't a player
+    app.world.spawn((
+        Name::new("Gull"),
+        MovementBundle::default(),
+        pirate_sim_core::goals::MovementGoal(Vec3::Y * 5.),
+        TransformBundle::from_transform(Transform::from_xyz(5., 0., 0.)),
+    ));
+
+    let mut reader = ManualEventReader::<PlayerMoved>::default();
+    let mut sent = Vec::new();
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..60 {
+        if app.world.get::<Transform>(player).unwrap().translation.x >= 1. {
+            break;
+        }
+
+        app.update();
+        sent.extend(
+            reader
+                .iter(app.world.resource::<Events<PlayerMoved>>())
+                .copied(),
+        );
+    }
+
+    // let it settle, to make sure nothing else is sent for the same tile
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::D);
+    for _ in 0..5 {
+        app.update();
+        sent.extend(
+            reader
+                .iter(app.world.resource::<Events<PlayerMoved>>())
+                .copied(),
+        );
+    }
+
+    assert_eq!(
+        sent,
+        vec![PlayerMoved {
+            tile: IVec3::new(1, 0, 0)
+        }]
+    );
+}
+
+/// Wander around a 5x5 region centred on the origin for ten seconds, returning every tile the
+/// wanderer was on
+fn wander_for_ten_seconds(seed: u64) -> Vec<IVec3> {
+    use pirate_sim_core::{
+        goals::MovementGoal,
+        random::Generator,
+        tile_grid::{GetTileLocation, TileRegion, TileStretch},
+    };
+
+    use bevy_ecs::schedule::IntoSystemConfigs;
+
+    use crate::npc::Wander;
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins(PhysicsPlugin {
+        delta_override: Some(1. / 60.),
+        ..Default::default()
+    });
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+    app.insert_resource(Generator::seeded(seed));
+    app.add_systems(
+        Update,
+        crate::npc::wander.in_set(pirate_sim_core::PhysicsSet::Input),
+    );
+
+    let region = TileRegion::new(IVec3::new(-2, -2, 0), IVec3::new(2, 2, 0));
+    let wanderer = app
+        .world
+        .spawn((
+            Name::new("Wanderer"),
+            MovementBundle::default(),
+            WalkSpeed(10.),
+            MovementGoal::default(),
+            Wander::new(region, 0.5),
+            TransformBundle::default(),
+        ))
+        .id();
+
+    (0..600)
+        .map(|_| {
+            app.update();
+
+            let location = app
+                .world
+                .get::<GlobalTransform>(wanderer)
+                .unwrap()
+                .location(TileStretch::new(1, 1));
+            assert!(region.contains(location), "wandered out to {location}");
+
+            location
+        })
+        .collect()
+}
+
+#[test]
+fn wanderers_stay_in_their_region() {
+    let path = wander_for_ten_seconds(7);
+
+    // it did actually wander
+    let mut visited = path.clone();
+    visited.sort_by_key(IVec3::to_array);
+    visited.dedup();
+    assert!(visited.len() > 5, "only visited {visited:?}");
+
+    // and the same seed wanders the same way
+    assert_eq!(path, wander_for_ten_seconds(7));
+}
+
+#[test]
+#[allow(clippy::float_cmp)]
+fn set_movement_changes_everything_at_once() {
+    use bevy_ecs::{prelude::*, system::EntityCommand};
+    use pirate_sim_core::goals::MovementGoal;
+    use pirate_sim_physics::movement::Facing;
+
+    use crate::{MovementGoalTimer, SetMovement};
+
+    #[derive(Resource, Default)]
+    struct Seen(Vec<(Vec3, f32, IVec3)>);
+
+    let mut app = App::new();
+    app.init_resource::<Seen>();
+
+    let mover = app
+        .world
+        .spawn((PlayerControllerBundle::default(), Facing::default()))
+        .id();
+
+    let set_movement = SetMovement {
+        goal: Vec3::NEG_X * 5.,
+        timeout: Some(0.5),
+        facing: Some(IVec3::NEG_X),
+    };
+    app.add_systems(
+        Update,
+        move |mut commands: Commands, mut sent: Local<bool>| {
+            if !*sent {
+                commands.entity(mover).add(set_movement);
+                *sent = true;
+            }
+        },
+    );
+    // anything running after the command is applied sees all of it at once
+    app.add_systems(
+        PostUpdate,
+        |changed_q: Query<
+            (&MovementGoal, &MovementGoalTimer, &Facing),
+            Or<(
+                Changed<MovementGoal>,
+                Changed<MovementGoalTimer>,
+                Changed<Facing>,
+            )>,
+        >,
+         mut seen: ResMut<Seen>| {
+            for (goal, timer, facing) in &changed_q {
+                seen.0
+                    .push((goal.0, timer.duration().as_secs_f32(), facing.0));
+            }
+        },
+    );
+
+    app.update();
+    app.update();
+
+    // spawning counts as a change, so the first frame sees the applied command
+    assert_eq!(
+        app.world.resource::<Seen>().0,
+        vec![(Vec3::NEG_X * 5., 0.5, IVec3::NEG_X)]
+    );
+
+    // without a timeout, the goal stays
+    SetMovement {
+        goal: Vec3::Y,
+        timeout: None,
+        facing: None,
+    }
+    .apply(mover, &mut app.world);
+
+    let timer = app.world.get::<MovementGoalTimer>(mover).unwrap();
+    assert!(timer.paused() && !timer.finished());
+    assert_eq!(app.world.get::<MovementGoal>(mover).unwrap().0, Vec3::Y);
+    assert_eq!(app.world.get::<Facing>(mover).unwrap().0, IVec3::NEG_X);
+}
+
+#[test]
+fn camera_glides_after_the_player() {
+    use bevy_render::prelude::Camera;
+
+    use crate::player::{camera_follow_player, CameraFollowConfig, Controller, FollowPlayer};
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+    app.insert_resource(CameraFollowConfig { smoothing: 0.5 });
+    app.add_systems(Update, camera_follow_player);
+
+    let player = app
+        .world
+        .spawn((Controller(), TransformBundle::default()))
+        .id();
+    // cameras sit above the level they look at
+    let camera = app
+        .world
+        .spawn((
+            Camera::default(),
+            FollowPlayer(),
+            TransformBundle::from_transform(Transform::from_xyz(0., 0., 2.)),
+        ))
+        .id();
+    let other_camera = app
+        .world
+        .spawn((Camera::default(), TransformBundle::default()))
+        .id();
+
+    // no time has passed yet on the first update
+    app.update();
+
+    let mut expected = 0.;
+    for tile in 1..=5_u8 {
+        let player_x = f32::from(tile);
+
+        // the player snaps a tile along every update
+        app.world
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation
+            .x = player_x;
+        app.update();
+
+        // and the camera closes half of the distance that's left each time
+        expected += (player_x - expected) * 0.5;
+
+        let camera_x = app.world.get::<Transform>(camera).unwrap().translation.x;
+        assert!(
+            (camera_x - expected).abs() < 1e-4,
+            "camera at {camera_x}, expected {expected}"
+        );
+        assert!(camera_x < player_x);
+        // staying at its own level
+        let camera_z = app.world.get::<Transform>(camera).unwrap().translation.z;
+        assert!((camera_z - 2.).abs() < 1e-4, "camera moved to z {camera_z}");
+    }
+
+    // only cameras marked to follow the player do
+    assert_eq!(
+        app.world
+            .get::<Transform>(other_camera)
+            .unwrap()
+            .translation,
+        Vec3::ZERO
+    );
+}
@@ -19,9 +19,11 @@ pub use thiserror;
 
 pub mod goals;
 pub mod random;
+pub mod spawn_budget;
 pub mod system_sets;
 
 pub mod tile_grid;
+pub mod timers;
 pub mod utils;
 
 pub use system_sets::PhysicsSet;
@@ -47,7 +49,26 @@ impl bevy_app::Plugin for CorePlugin {
 
 /// A plugin that sets up things that nearly every system expects to exist, for quick test setup
 pub mod test_utils {
-    pub struct DefaultTestPlugin;
+    /// Inserts a 1<->1 [`TileStretch`] by default, for simplicity. Use
+    /// [`DefaultTestPlugin::with_stretch`] to test with anything else.
+    pub struct DefaultTestPlugin {
+        tile_stretch: TileStretch,
+    }
+
+    impl DefaultTestPlugin {
+        #[must_use]
+        pub fn with_stretch(x: u8, y: u8) -> Self {
+            Self {
+                tile_stretch: TileStretch::new(x, y),
+            }
+        }
+    }
+
+    impl Default for DefaultTestPlugin {
+        fn default() -> Self {
+            Self::with_stretch(1, 1)
+        }
+    }
 
     use crate::tile_grid::TileStretch;
     use bevy_app::prelude::*;
@@ -58,8 +79,7 @@ pub mod test_utils {
 
     impl Plugin for DefaultTestPlugin {
         fn build(&self, app: &mut App) {
-            // 1<->1 conversion for simplicity
-            app.insert_resource(TileStretch::new(1, 1));
+            app.insert_resource(self.tile_stretch);
 
             // system to log location of every named entity when it moves
             app.add_systems(
@@ -16,6 +16,14 @@ pub struct Seed(u64);
 #[derive(Resource, Deref, DerefMut)]
 pub struct Generator(RandomNumberGenerator);
 
+impl Generator {
+    /// A generator seeded with `seed`, for reproducible results, eg. in tests
+    #[must_use]
+    pub fn seeded(seed: u64) -> Self {
+        Self(RandomNumberGenerator::seeded(seed))
+    }
+}
+
 pub fn setup_generator(mut commands: Commands) {
     let seed = std::time::SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -23,5 +31,5 @@ pub fn setup_generator(mut commands: Commands) {
         .as_secs();
 
     commands.insert_resource(Seed(seed));
-    commands.insert_resource(Generator(RandomNumberGenerator::seeded(seed)));
+    commands.insert_resource(Generator::seeded(seed));
 }
@@ -0,0 +1,104 @@
+//! A cap on how many entities can be spawned each frame, to catch runaway spawning during
+//! development
+//!
+//! Spawns only count against the [`SpawnBudget`] when they go through the [`SpawnTracked`]
+//! command. Once the budget is used up, any further tracked spawns that frame are dropped and a
+//! [`SpawnBudgetExceeded`] is sent. Authored content, like levels and ships, should never be
+//! tracked, so that none of it can be dropped.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+
+/// How many tracked spawns are allowed per frame, and how many have happened so far this frame.
+///
+/// Without this resource, [`SpawnTracked`] spawns are never limited.
+#[derive(Resource, Debug, Clone)]
+pub struct SpawnBudget {
+    pub per_frame: usize,
+    spawned: usize,
+    blocked: usize,
+}
+
+impl SpawnBudget {
+    #[must_use]
+    pub fn new(per_frame: usize) -> Self {
+        Self {
+            per_frame,
+            spawned: 0,
+            blocked: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn spawned_this_frame(&self) -> usize {
+        self.spawned
+    }
+
+    /// How many spawns have been dropped this frame for going over budget
+    #[must_use]
+    pub fn blocked_this_frame(&self) -> usize {
+        self.blocked
+    }
+}
+
+/// Sent the first time a tracked spawn is blocked in a frame
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnBudgetExceeded {
+    pub per_frame: usize,
+}
+
+/// A command to spawn `0`, as long as the [`SpawnBudget`] for this frame hasn't been used up
+pub struct SpawnTracked<B: Bundle>(pub B);
+
+impl<B: Bundle> bevy_ecs::system::Command for SpawnTracked<B> {
+    fn apply(self, world: &mut World) {
+        if let Some(mut budget) = world.get_resource_mut::<SpawnBudget>() {
+            if budget.spawned >= budget.per_frame {
+                budget.blocked += 1;
+
+                if budget.blocked == 1 {
+                    let per_frame = budget.per_frame;
+
+                    warn!(
+                        "spawn budget of {per_frame} per frame exceeded; blocking further spawns"
+                    );
+                    world.send_event(SpawnBudgetExceeded { per_frame });
+                }
+
+                return;
+            }
+
+            budget.spawned += 1;
+        }
+
+        world.spawn(self.0);
+    }
+}
+
+/// Start each frame with a fresh budget
+pub fn reset_spawn_budget(budget: Option<ResMut<SpawnBudget>>) {
+    let Some(mut budget) = budget else {
+        return;
+    };
+
+    if budget.blocked > 0 {
+        debug!("{} spawns were blocked last frame", budget.blocked);
+    }
+
+    budget.spawned = 0;
+    budget.blocked = 0;
+}
+
+/// Sets up a [`SpawnBudget`] of `per_frame` tracked spawns, reset at the start of each frame
+pub struct SpawnBudgetPlugin {
+    pub per_frame: usize,
+}
+
+impl Plugin for SpawnBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpawnBudget::new(self.per_frame))
+            .add_event::<SpawnBudgetExceeded>()
+            .add_systems(First, reset_spawn_budget);
+    }
+}
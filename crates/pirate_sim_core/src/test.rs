@@ -7,12 +7,18 @@ use bevy_log::prelude::*;
 use bevy_math::{IVec3, Vec3};
 use bevy_transform::prelude::*;
 
-use crate::tile_grid::TileStretch;
+use crate::tile_grid::{
+    are_on_grid, chebyshev, checked_tile_add, checked_tile_mul, flood_fill, is_on_grid, line,
+    manhattan, neighbors, tiles_in, Connectivity, GetTileLocation, Rounding, TileRegion,
+    TileStretch,
+};
+use crate::timers::{TimerPool, TimerPoolPlugin};
+use crate::utils::{approx_eq, approx_eq_vec3, TOLERANCE};
 
 #[test]
 fn tile_stretch_round_trip() {
     let start = Vec3::new(32., 64., 3.);
-    let tile_stretch = TileStretch(32, 32);
+    let tile_stretch = TileStretch::new(32, 32);
 
     let cast_to_grid = tile_stretch
         .get_tile(start)
@@ -28,7 +34,7 @@ fn tile_stretch_round_trip() {
 #[test]
 fn tile_stretch_fail_off_grid() {
     let start = Vec3::new(33., 64., 3.);
-    let tile_stretch = TileStretch(32, 32);
+    let tile_stretch = TileStretch::new(32, 32);
 
     let cast_to_grid = tile_stretch.get_tile(start);
 
@@ -41,3 +47,481 @@ fn tile_stretch_fail_off_grid() {
     assert_eq!(closest, IVec3::new(1, 2, 3));
     assert_eq!(tile_stretch.get_closest(start), closest);
 }
+
+#[test]
+fn on_grid_exact() {
+    let tile_stretch = TileStretch::new(32, 32);
+    let transform = GlobalTransform::from_translation(Vec3::new(64., -32., 2.));
+
+    assert!(is_on_grid(&transform, tile_stretch));
+}
+
+#[test]
+fn on_grid_fractional() {
+    let tile_stretch = TileStretch::new(32, 32);
+    let transform = GlobalTransform::from_translation(Vec3::new(64.5, 32., 2.));
+
+    assert!(!is_on_grid(&transform, tile_stretch));
+}
+
+#[test]
+fn on_grid_not_multiple_of_stretch() {
+    let tile_stretch = TileStretch::new(32, 16);
+    let transform = GlobalTransform::from_translation(Vec3::new(32., 24., 0.));
+
+    assert!(!is_on_grid(&transform, tile_stretch));
+}
+
+#[test]
+fn transform_location_matches_global_transform() {
+    let tile_stretch = TileStretch::new(32, 16);
+
+    for translation in [
+        Vec3::new(64., -32., 2.),
+        Vec3::new(64.5, 32., 2.),
+        Vec3::new(-40., 24., -1.),
+        Vec3::ZERO,
+    ] {
+        let transform = Transform::from_translation(translation);
+        let global = GlobalTransform::from_translation(translation);
+
+        assert_eq!(
+            transform.location(tile_stretch),
+            global.location(tile_stretch)
+        );
+        assert_eq!(
+            <&Transform as GetTileLocation>::location(&&transform, tile_stretch),
+            <&GlobalTransform as GetTileLocation>::location(&&global, tile_stretch)
+        );
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn tile_stretch_tuple_still_works() {
+    let tile_stretch = TileStretch::new(16, 32);
+
+    assert_eq!(tile_stretch.tuple(), (16, 32));
+    assert_eq!(tile_stretch.tuple().1, tile_stretch.y);
+}
+
+#[test]
+fn test_plugin_uses_requested_stretch() {
+    let mut app = App::new();
+
+    app.add_plugins(crate::test_utils::DefaultTestPlugin::with_stretch(16, 32));
+
+    let tile_stretch = *app.world.resource::<TileStretch>();
+
+    assert_eq!((tile_stretch.x, tile_stretch.y), (16, 32));
+}
+
+#[test]
+fn get_closest_floors_negative_coordinates() {
+    let tile_stretch = TileStretch::new(32, 32);
+
+    for (x, tile) in [(-32., -1), (-31., -1), (-1., -1), (33., 1)] {
+        assert_eq!(
+            tile_stretch.get_closest(Vec3::new(x, x, 0.)),
+            IVec3::new(tile, tile, 0),
+            "{x} should be on tile {tile}"
+        );
+    }
+}
+
+#[test]
+fn tile_stretch_rounding_modes() {
+    let tile_stretch = TileStretch::new(32, 32);
+
+    // x, floor, nearest, ceil, toward zero
+    let table = [
+        (15.9, 0, 0, 1, 0),
+        (16.1, 0, 1, 1, 0),
+        (-16.1, -1, -1, 0, 0),
+        (-32., -1, -1, -1, -1),
+    ];
+
+    for (x, floor, nearest, ceil, toward_zero) in table {
+        let rounded = |mode| tile_stretch.get_tile_rounded(Vec3::new(x, 0., 0.), mode).x;
+
+        assert_eq!(rounded(Rounding::Floor), floor, "floor {x}");
+        assert_eq!(rounded(Rounding::Nearest), nearest, "nearest {x}");
+        assert_eq!(rounded(Rounding::Ceil), ceil, "ceil {x}");
+        assert_eq!(
+            rounded(Rounding::TowardZero),
+            toward_zero,
+            "toward zero {x}"
+        );
+
+        assert_eq!(tile_stretch.get_closest(Vec3::new(x, 0., 0.)).x, floor);
+    }
+}
+
+#[test]
+fn tile_stretch_fractional_round_trip() {
+    let tile_stretch = TileStretch::new(16, 32);
+
+    for start in [
+        Vec3::new(8., 48., 0.5),
+        Vec3::new(-3.25, 100.1, -2.),
+        Vec3::ZERO,
+    ] {
+        let tile_f = tile_stretch.get_tile_f(start);
+        // multiplying is the float version of get_bevy
+        let back = tile_stretch * tile_f;
+
+        assert!(
+            approx_eq_vec3(back, start, TOLERANCE),
+            "{start} came back as {back}"
+        );
+    }
+
+    assert!(approx_eq_vec3(
+        tile_stretch.get_tile_f(Vec3::new(8., 48., 1.)),
+        Vec3::new(0.5, 1.5, 1.),
+        TOLERANCE
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn tile_stretch_serde_round_trip() {
+    let tile_stretch = TileStretch::new(16, 32);
+
+    let json = serde_json::to_string(&tile_stretch).expect("should serialize to json");
+    assert_eq!(json, r#"{"x":16,"y":32}"#);
+    assert_eq!(
+        serde_json::from_str::<TileStretch>(&json).expect("should deserialize from json"),
+        tile_stretch
+    );
+
+    let ron = ron::to_string(&tile_stretch).expect("should serialize to ron");
+    assert_eq!(
+        ron::from_str::<TileStretch>(&ron).expect("should deserialize from ron"),
+        tile_stretch
+    );
+}
+
+#[test]
+fn tile_stretch_batched_conversions() {
+    let tile_stretch = TileStretch::new(16, 32);
+    let tiles = [
+        IVec3::new(0, 0, 0),
+        IVec3::new(1, 2, 3),
+        IVec3::new(-1, -1, 0),
+    ];
+
+    let translations = tile_stretch.get_bevy_many(&tiles);
+
+    assert_eq!(
+        translations,
+        tiles.map(|tile| tile_stretch.get_bevy(tile)).to_vec()
+    );
+    assert_eq!(tile_stretch.get_closest_many(&translations), tiles.to_vec());
+    assert!(tile_stretch
+        .get_closest_iter(tile_stretch.get_bevy_iter(tiles))
+        .eq(tiles));
+}
+
+#[test]
+fn tiles_in_walks_the_whole_box() {
+    let min = IVec3::new(-1, 0, 2);
+    let max = IVec3::new(2, 2, 3);
+
+    let tiles: Vec<_> = tiles_in(min, max).collect();
+
+    assert_eq!(tiles.len(), 4 * 3 * 2);
+    assert!(tiles
+        .iter()
+        .all(|tile| TileRegion::new(min, max).contains(*tile)));
+
+    // x first, then y, then z
+    assert_eq!(
+        tiles[..5],
+        [
+            IVec3::new(-1, 0, 2),
+            IVec3::new(0, 0, 2),
+            IVec3::new(1, 0, 2),
+            IVec3::new(2, 0, 2),
+            IVec3::new(-1, 1, 2),
+        ]
+    );
+    assert_eq!(tiles.last(), Some(&max));
+    assert!(tiles_in(min, max).eq(tiles));
+
+    // backwards along any axis is empty
+    assert_eq!(
+        tiles_in(IVec3::new(0, 1, 0), IVec3::new(3, 0, 3)).count(),
+        0
+    );
+}
+
+#[test]
+fn checked_tile_arithmetic() {
+    let a = IVec3::new(3, -4, 1);
+    let b = IVec3::new(-10, 2, 5);
+
+    assert_eq!(checked_tile_add(a, b), Some(a + b));
+    assert_eq!(checked_tile_mul(a, b), Some(a * b));
+
+    // a single overflowing axis is enough
+    assert_eq!(checked_tile_add(IVec3::new(i32::MAX, 0, 0), IVec3::X), None);
+    assert_eq!(
+        checked_tile_add(IVec3::new(0, 0, i32::MIN), IVec3::NEG_Z),
+        None
+    );
+    assert_eq!(
+        checked_tile_mul(IVec3::new(1, i32::MAX / 2 + 1, 1), IVec3::splat(2)),
+        None
+    );
+    assert_eq!(
+        checked_tile_mul(IVec3::splat(i32::MIN), IVec3::NEG_ONE),
+        None
+    );
+}
+
+#[test]
+fn tile_distances() {
+    // a, b, manhattan, chebyshev
+    let pairs = [
+        (IVec3::ZERO, IVec3::ZERO, 0, 0),
+        (IVec3::new(1, 2, 3), IVec3::new(4, 6, 3), 7, 4),
+        (IVec3::new(-2, 0, 1), IVec3::new(2, -1, -4), 10, 5),
+        (IVec3::new(0, 0, -3), IVec3::new(1, 1, 0), 5, 3),
+    ];
+
+    for (a, b, expected_manhattan, expected_chebyshev) in pairs {
+        assert_eq!(manhattan(a, b), expected_manhattan, "manhattan {a} {b}");
+        assert_eq!(manhattan(b, a), expected_manhattan, "manhattan {b} {a}");
+        assert_eq!(chebyshev(a, b), expected_chebyshev, "chebyshev {a} {b}");
+        assert_eq!(chebyshev(b, a), expected_chebyshev, "chebyshev {b} {a}");
+    }
+}
+
+#[test]
+fn on_grid_batched() {
+    let tile_stretch = TileStretch::new(32, 32);
+    let transforms = [
+        GlobalTransform::from_translation(Vec3::new(32., 32., 0.)),
+        GlobalTransform::from_translation(Vec3::new(32.5, 32., 0.)),
+        GlobalTransform::from_translation(Vec3::new(33., 32., 0.)),
+    ];
+
+    let results: Vec<_> = are_on_grid(transforms.iter().enumerate(), tile_stretch).collect();
+
+    assert_eq!(results, vec![(0, true), (1, false), (2, false)]);
+}
+
+#[test]
+fn neighbors_by_connectivity() {
+    let tile = IVec3::new(3, -2, 1);
+
+    for (connectivity, count) in [
+        (Connectivity::Four, 4),
+        (Connectivity::Eight, 8),
+        (Connectivity::TwentySix, 26),
+    ] {
+        let found: Vec<_> = neighbors(tile, connectivity).collect();
+
+        assert_eq!(found.len(), count, "{connectivity:?}");
+        assert!(!found.contains(&tile), "{connectivity:?}");
+        assert!(found.iter().all(|n| chebyshev(*n, tile) == 1));
+
+        if connectivity != Connectivity::TwentySix {
+            assert!(found.iter().all(|n| n.z == tile.z), "{connectivity:?}");
+        }
+    }
+
+    assert!(neighbors(tile, Connectivity::Four).all(|n| manhattan(n, tile) == 1));
+}
+
+#[test]
+fn line_is_contiguous() {
+    let cases = [
+        (IVec3::ZERO, IVec3::new(7, 3, 0)),
+        (IVec3::new(2, -4, 1), IVec3::new(-5, 6, -3)),
+        (IVec3::new(1, 1, 1), IVec3::new(-2, -9, 4)),
+        (IVec3::ZERO, IVec3::new(0, -6, 0)),
+    ];
+
+    for (start, end) in cases {
+        let tiles: Vec<_> = line(start, end).collect();
+
+        assert_eq!(tiles.first(), Some(&start));
+        assert_eq!(tiles.last(), Some(&end));
+        assert_eq!(
+            tiles.len(),
+            usize::try_from(chebyshev(start, end) + 1).expect("distances aren't negative")
+        );
+        assert!(
+            tiles
+                .windows(2)
+                .all(|pair| chebyshev(pair[0], pair[1]) == 1),
+            "{start} to {end} has a gap: {tiles:?}"
+        );
+    }
+
+    // straight along an axis is exactly the span
+    assert_eq!(
+        line(IVec3::new(0, 0, 2), IVec3::new(0, 0, 5)).collect::<Vec<_>>(),
+        (2..=5).map(|z| IVec3::new(0, 0, z)).collect::<Vec<_>>()
+    );
+
+    let tile = IVec3::new(4, -1, 2);
+    assert_eq!(line(tile, tile).collect::<Vec<_>>(), vec![tile]);
+}
+
+#[test]
+fn flood_fill_open_region() {
+    // a 3x3 open square
+    let filled = flood_fill(
+        IVec3::new(1, 1, 0),
+        |t| (0..3).contains(&t.x) && (0..3).contains(&t.y) && t.z == 0,
+        100,
+    );
+
+    assert_eq!(filled.len(), 9);
+    assert!(filled.contains(&IVec3::ZERO));
+    assert!(filled.contains(&IVec3::new(2, 2, 0)));
+}
+
+#[test]
+fn flood_fill_bounded_by_walls() {
+    // a wall along x = 2 splits the plane, and the fill should only find the left of it
+    let filled = flood_fill(
+        IVec3::ZERO,
+        |t| t.x != 2 && (-1..2).contains(&t.y) && (-5..5).contains(&t.x),
+        100,
+    );
+
+    assert_eq!(filled.len(), 7 * 3);
+    assert!(filled.iter().all(|t| t.x < 2));
+    assert!(!filled.contains(&IVec3::new(3, 0, 0)));
+
+    // diagonal steps shouldn't leak through a corner
+    let filled = flood_fill(IVec3::ZERO, |t| t == IVec3::ZERO || t == IVec3::ONE, 100);
+
+    assert_eq!(filled.len(), 1);
+}
+
+#[test]
+fn flood_fill_max_tiles() {
+    let filled = flood_fill(IVec3::ZERO, |_| true, 50);
+
+    assert_eq!(filled.len(), 50);
+
+    assert!(flood_fill(IVec3::ZERO, |_| true, 0).is_empty());
+    assert!(flood_fill(IVec3::ZERO, |_| false, 50).is_empty());
+}
+
+#[test]
+fn approx_eq_floats() {
+    assert!(approx_eq(1., 1., TOLERANCE));
+    assert!(approx_eq(-3., -3. + TOLERANCE / 2., TOLERANCE));
+    assert!(!approx_eq(-3., 3., TOLERANCE));
+    assert!(!approx_eq(0.1, -0.1, TOLERANCE));
+
+    // either side of the tolerance boundary
+    assert!(approx_eq(0., 0.5, 0.5));
+    assert!(approx_eq(-0.5, 0., 0.5));
+    assert!(!approx_eq(0., 0.51, 0.5));
+    assert!(!approx_eq(-0.51, 0., 0.5));
+}
+
+#[test]
+fn approx_eq_vec3s() {
+    assert!(approx_eq_vec3(
+        Vec3::new(1., -2., 3.),
+        Vec3::new(1., -2., 3.) + TOLERANCE / 2.,
+        TOLERANCE
+    ));
+    assert!(approx_eq_vec3(Vec3::NEG_ONE, Vec3::ZERO, 1.));
+
+    // a single axis being off should fail the whole comparison
+    assert!(!approx_eq_vec3(
+        Vec3::new(1., -2., 3.),
+        Vec3::new(1., -2.1, 3.),
+        TOLERANCE
+    ));
+    assert!(!approx_eq_vec3(Vec3::NEG_ONE, Vec3::ONE, 1.));
+}
+
+#[test]
+fn timer_pool_expires_in_order() {
+    use std::time::Duration;
+
+    let mut app = App::new();
+
+    app.add_plugins((bevy_time::TimePlugin, TimerPoolPlugin));
+    app.insert_resource(bevy_time::TimeUpdateStrategy::ManualDuration(
+        Duration::from_millis(100),
+    ));
+
+    let mut pool = app.world.resource_mut::<TimerPool>();
+    let durations = [500, 150, 1000, 350];
+    let handles: Vec<_> = durations
+        .map(|millis| pool.insert(Duration::from_millis(millis)))
+        .into();
+
+    // removed timers shouldn't finish, and their slot can be reused without confusing handles
+    let removed = pool.insert(Duration::from_millis(50));
+    assert!(pool.remove(removed).is_some());
+    assert!(pool.remove(removed).is_none());
+    assert!(pool.finished(removed));
+
+    let mut finished_order = Vec::new();
+    for _ in 0..12 {
+        app.update();
+
+        finished_order.extend(
+            app.world
+                .resource::<TimerPool>()
+                .just_finished()
+                .filter_map(|handle| handles.iter().position(|h| *h == handle)),
+        );
+    }
+
+    // sorted by duration
+    assert_eq!(finished_order, vec![1, 3, 0, 2]);
+    assert!(handles
+        .iter()
+        .all(|handle| app.world.resource::<TimerPool>().finished(*handle)));
+}
+
+#[test]
+fn spawn_budget_blocks_runaway_spawns() {
+    use crate::spawn_budget::{SpawnBudget, SpawnBudgetExceeded, SpawnBudgetPlugin, SpawnTracked};
+
+    #[derive(Component)]
+    struct Spawned;
+
+    let mut app = App::new();
+
+    app.add_plugins(SpawnBudgetPlugin { per_frame: 3 });
+    app.add_systems(Update, |mut commands: Commands| {
+        for _ in 0..5 {
+            commands.add(SpawnTracked(Spawned));
+        }
+    });
+
+    app.update();
+
+    let mut spawned_q = app.world.query_filtered::<(), With<Spawned>>();
+    assert_eq!(spawned_q.iter(&app.world).count(), 3);
+
+    let budget = app.world.resource::<SpawnBudget>();
+    assert_eq!(budget.spawned_this_frame(), 3);
+    assert_eq!(budget.blocked_this_frame(), 2);
+
+    // only warned about once per frame
+    let exceeded: Vec<_> = app
+        .world
+        .resource_mut::<Events<SpawnBudgetExceeded>>()
+        .drain()
+        .collect();
+    assert_eq!(exceeded, vec![SpawnBudgetExceeded { per_frame: 3 }]);
+
+    // the budget is refreshed next frame
+    app.update();
+    assert_eq!(spawned_q.iter(&app.world).count(), 6);
+}
@@ -7,24 +7,28 @@
 //! to allow any system that wishes to to work solely at the tilespace level.
 //!
 //! If something is "on grid" then that means its global transform's x is a multiple of
-//! [`TileStretch`].0 and its y is a multiple of [`TileStretch`].1. Its Z should be a whole number.
+//! [`TileStretch::x`] and its y is a multiple of [`TileStretch::y`]. Its Z should be a whole number.
 //! There should only be one [`TileStretch`] per world, as there is only one spritesheet loaded.
 
 use bevy_ecs::prelude::*;
 use bevy_math::prelude::*;
 use bevy_reflect::{GetTypeRegistration, Reflect};
-use bevy_transform::prelude::GlobalTransform;
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_utils::HashSet;
 use thiserror::Error;
 
 /// A resource storing the area of each sprite in the spritesheet. Nearly any conversion between
 /// [`IVec3`]<->[`Vec3`] should be done trough [`TileStretch`] to ensure that sprites are being displayed within
 /// the right grid.
 ///
-/// `Self::0` is x, `Self::1` is y
-///
-/// TODO: change from tuple fields to named x,y
-#[derive(Resource, Clone, Copy, Reflect, Debug)]
-pub struct TileStretch(pub u8, pub u8);
+/// `x` and `y` used to be the tuple fields `0` and `1`. [`TileStretch::tuple`] still gives them in
+/// that order while callers migrate, but is deprecated.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Reflect, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileStretch {
+    pub x: u8,
+    pub y: u8,
+}
 
 impl From<IVec2> for TileStretch {
     #[inline]
@@ -45,21 +49,47 @@ impl From<UVec2> for TileStretch {
 impl From<TileStretch> for IVec2 {
     #[inline]
     fn from(value: TileStretch) -> Self {
-        Self::new(i32::from(value.0), i32::from(value.1))
+        Self::new(i32::from(value.x), i32::from(value.y))
     }
 }
 
 impl From<TileStretch> for UVec2 {
     #[inline]
     fn from(value: TileStretch) -> Self {
-        Self::new(u32::from(value.0), u32::from(value.1))
+        Self::new(u32::from(value.x), u32::from(value.y))
     }
 }
 
 impl From<TileStretch> for Vec2 {
     #[inline]
     fn from(value: TileStretch) -> Self {
-        Self::new(f32::from(value.0), f32::from(value.1))
+        Self::new(f32::from(value.x), f32::from(value.y))
+    }
+}
+
+/// How to round a translation that lies partway between tiles. See
+/// [`TileStretch::get_tile_rounded`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum Rounding {
+    /// The tile containing the translation. Used by [`TileStretch::get_closest`]
+    #[default]
+    Floor,
+    /// The nearest tile, with halfway rounded away from zero
+    Nearest,
+    Ceil,
+    TowardZero,
+}
+
+impl Rounding {
+    #[must_use]
+    #[inline]
+    pub fn round(self, tile_f: Vec3) -> Vec3 {
+        match self {
+            Self::Floor => tile_f.floor(),
+            Self::Nearest => tile_f.round(),
+            Self::Ceil => tile_f.ceil(),
+            Self::TowardZero => tile_f.trunc(),
+        }
     }
 }
 
@@ -68,7 +98,7 @@ impl From<TileStretch> for Vec2 {
 /// Can only originate from [`TileStretch::get_tile`], and is bound to the lifetime of the two
 /// arguments of that function.
 #[derive(Error, Debug, Clone, Copy)]
-#[error("Coordinates {to_translate} not divisible by stretch {:?}",tile_stretch.0)]
+#[error("Coordinates {to_translate} not divisible by stretch {tile_stretch:?}")]
 pub struct GetTileError {
     to_translate: Vec3,
     tile_stretch: TileStretch,
@@ -95,15 +125,30 @@ impl GetTileError {
 }
 
 impl TileStretch {
-    /// returns closest tile from a bevy translation
+    /// The old tuple layout, `(x, y)`
+    #[deprecated(note = "use `TileStretch::x` and `TileStretch::y` instead")]
+    #[must_use]
+    #[inline]
+    pub const fn tuple(self) -> (u8, u8) {
+        (self.x, self.y)
+    }
+
+    /// returns the tile containing a bevy translation
+    ///
+    /// This floors rather than truncating toward zero, so that anything just left of or below the
+    /// origin lands on tile -1 rather than 0. See [`TileStretch::get_tile_rounded`] for other
+    /// rounding.
     #[must_use]
     #[inline]
     pub fn get_closest(self, t: Vec3) -> IVec3 {
-        IVec3::new(
-            t.x as i32 / i32::from(self.0),
-            t.y as i32 / i32::from(self.1),
-            t.z as i32,
-        )
+        self.get_tile_rounded(t, Rounding::Floor)
+    }
+
+    /// The tile for a bevy translation, rounding partway positions with `mode`
+    #[must_use]
+    #[inline]
+    pub fn get_tile_rounded(self, t: Vec3, mode: Rounding) -> IVec3 {
+        mode.round(self.get_tile_f(t)).as_ivec3()
     }
 
     /// Fallible translation from bevy-space to tilespace.
@@ -117,8 +162,8 @@ impl TileStretch {
     #[inline]
     pub fn get_tile(self, t: Vec3) -> Result<IVec3, GetTileError> {
         if t.round() != t
-            || t.x as i32 % i32::from(self.0) != 0
-            || t.y as i32 % i32::from(self.1) != 0
+            || t.x as i32 % i32::from(self.x) != 0
+            || t.y as i32 % i32::from(self.y) != 0
         {
             Err(GetTileError::new(t, self))
         } else {
@@ -152,23 +197,269 @@ impl TileStretch {
         #[allow(clippy::cast_precision_loss)]
         // TODO: do like unity and check for if it's above 1 << 23
         Vec3::new(
-            t.x as f32 * f32::from(self.0),
-            t.y as f32 * f32::from(self.1),
+            t.x as f32 * f32::from(self.x),
+            t.y as f32 * f32::from(self.y),
             t.z as f32,
         )
     }
 
+    /// The continuous tile-space position of a bevy translation, without rounding to a tile. This
+    /// is useful for interpolating between tiles.
+    ///
+    /// Multiplying the result by `self` gets back the original translation.
+    #[must_use]
+    #[inline]
+    pub fn get_tile_f(self, t: Vec3) -> Vec3 {
+        t / Vec3::new(f32::from(self.x), f32::from(self.y), 1.)
+    }
+
+    /// [`TileStretch::get_bevy`] for many tiles at once
+    #[must_use]
+    pub fn get_bevy_many(self, tiles: &[IVec3]) -> Vec<Vec3> {
+        self.get_bevy_iter(tiles.iter().copied()).collect()
+    }
+
+    /// [`TileStretch::get_closest`] for many translations at once
+    #[must_use]
+    pub fn get_closest_many(self, translations: &[Vec3]) -> Vec<IVec3> {
+        self.get_closest_iter(translations.iter().copied())
+            .collect()
+    }
+
+    /// A non-allocating [`TileStretch::get_bevy_many`]
+    #[inline]
+    pub fn get_bevy_iter(
+        self,
+        tiles: impl IntoIterator<Item = IVec3>,
+    ) -> impl Iterator<Item = Vec3> {
+        tiles.into_iter().map(move |tile| self.get_bevy(tile))
+    }
+
+    /// A non-allocating [`TileStretch::get_closest_many`]
+    #[inline]
+    pub fn get_closest_iter(
+        self,
+        translations: impl IntoIterator<Item = Vec3>,
+    ) -> impl Iterator<Item = IVec3> {
+        translations
+            .into_iter()
+            .map(move |translation| self.get_closest(translation))
+    }
+
     #[must_use]
     #[inline]
     pub fn new(x: u8, y: u8) -> Self {
-        Self(x, y)
+        Self { x, y }
+    }
+}
+
+/// An axis aligned box of tiles, including both `min` and `max`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub struct TileRegion {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl TileRegion {
+    /// The region between two opposite corners, in any order
+    #[must_use]
+    #[inline]
+    pub fn new(a: IVec3, b: IVec3) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Whether or not `tile` lies within this region, including its edges
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, tile: IVec3) -> bool {
+        tile.cmpge(self.min).all() && tile.cmple(self.max).all()
+    }
+
+    /// Every tile in this region. See [`tiles_in`]
+    #[inline]
+    pub fn tiles(&self) -> impl Iterator<Item = IVec3> {
+        tiles_in(self.min, self.max)
+    }
+}
+
+/// Every tile in the box between `min` and `max`, inclusive, walking x first, then y, then z.
+///
+/// Unlike [`TileRegion::new`] the corners aren't sorted, so if `min` is greater than `max` along
+/// any axis then nothing is yielded.
+pub fn tiles_in(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+    (min.z..=max.z).flat_map(move |z| {
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+    })
+}
+
+/// Sent whenever a tracked entity's tile changes, whether it moved itself or was carried along by
+/// its parent. See `pirate_sim_physics::movement::LastTile` for which entities are tracked.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileChanged {
+    pub entity: Entity,
+    pub from: IVec3,
+    pub to: IVec3,
+}
+
+/// Returns true if `transform` lies exactly on grid. See [`TileStretch::get_tile`]
+#[must_use]
+#[inline]
+pub fn is_on_grid(transform: &GlobalTransform, tile_stretch: TileStretch) -> bool {
+    tile_stretch.get_tile(transform.translation()).is_ok()
+}
+
+/// [`is_on_grid`] for many transforms at once, ie. every transform in a query.
+///
+/// Returns each item's data alongside whether or not it is on grid, so that off grid entities
+/// can be reported or snapped.
+pub fn are_on_grid<Data, Transform>(
+    transforms: impl Iterator<Item = (Data, Transform)>,
+    tile_stretch: TileStretch,
+) -> impl Iterator<Item = (Data, bool)>
+where
+    Transform: std::borrow::Borrow<GlobalTransform>,
+{
+    transforms.map(move |(data, transform)| (data, is_on_grid(transform.borrow(), tile_stretch)))
+}
+
+/// `a + b`, or `None` if any axis would overflow rather than wrapping
+#[must_use]
+#[inline]
+pub fn checked_tile_add(a: IVec3, b: IVec3) -> Option<IVec3> {
+    Some(IVec3::new(
+        a.x.checked_add(b.x)?,
+        a.y.checked_add(b.y)?,
+        a.z.checked_add(b.z)?,
+    ))
+}
+
+/// `a * b` along each axis, or `None` if any axis would overflow rather than wrapping
+#[must_use]
+#[inline]
+pub fn checked_tile_mul(a: IVec3, b: IVec3) -> Option<IVec3> {
+    Some(IVec3::new(
+        a.x.checked_mul(b.x)?,
+        a.y.checked_mul(b.y)?,
+        a.z.checked_mul(b.z)?,
+    ))
+}
+
+/// The distance between two tiles when only moving along one axis at a time
+#[must_use]
+#[inline]
+pub fn manhattan(a: IVec3, b: IVec3) -> i32 {
+    let difference = (a - b).abs();
+    difference.x + difference.y + difference.z
+}
+
+/// The distance between two tiles when diagonal steps, including along z, are as cheap as straight
+/// ones
+#[must_use]
+#[inline]
+pub fn chebyshev(a: IVec3, b: IVec3) -> i32 {
+    (a - b).abs().max_element()
+}
+
+/// Every tile on the straight line from `start` to `end`, including both.
+///
+/// This is a Bresenham-style walk, so each tile is one step from the last along every axis and
+/// there are always [`chebyshev`]`(start, end) + 1` tiles. If `start` and `end` are the same tile
+/// then only it is yielded.
+pub fn line(start: IVec3, end: IVec3) -> impl Iterator<Item = IVec3> {
+    let delta = end - start;
+    let steps = chebyshev(start, end);
+
+    (0..=steps).map(move |step| {
+        if steps == 0 {
+            return start;
+        }
+
+        // delta * step / steps, rounded to the nearest tile
+        let numerator = delta * 2 * step + IVec3::splat(steps);
+        start
+            + IVec3::new(
+                numerator.x.div_euclid(2 * steps),
+                numerator.y.div_euclid(2 * steps),
+                numerator.z.div_euclid(2 * steps),
+            )
+    })
+}
+
+/// Which tiles count as being next to each other. See [`neighbors`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum Connectivity {
+    /// Sharing an edge on the same z level
+    Four,
+    /// Sharing an edge or a corner on the same z level
+    Eight,
+    /// Touching in any way, including on the z levels above and below
+    TwentySix,
+}
+
+impl Connectivity {
+    /// Whether `offset`, which must be within one tile along every axis, is a neighbor
+    #[inline]
+    fn connects(self, offset: IVec3) -> bool {
+        match self {
+            Self::Four => offset.z == 0 && manhattan(offset, IVec3::ZERO) == 1,
+            Self::Eight => offset.z == 0,
+            Self::TwentySix => true,
+        }
     }
 }
 
+/// Every tile next to `tile`, not including `tile` itself
+pub fn neighbors(tile: IVec3, connectivity: Connectivity) -> impl Iterator<Item = IVec3> {
+    tiles_in(IVec3::NEG_ONE, IVec3::ONE)
+        .filter(move |offset| *offset != IVec3::ZERO && connectivity.connects(*offset))
+        .map(move |offset| tile + offset)
+}
+
+/// Every tile reachable from `start` by walking between 4-connected neighbors on the same z
+/// level, where each tile walked onto satisfies `is_passable`.
+///
+/// At most `max_tiles` tiles are visited, so that an unbounded region can't run forever. If
+/// `start` isn't passable then nothing is reachable and the set is empty.
+pub fn flood_fill(
+    start: IVec3,
+    is_passable: impl Fn(IVec3) -> bool,
+    max_tiles: usize,
+) -> HashSet<IVec3> {
+    let mut filled = HashSet::new();
+
+    if max_tiles == 0 || !is_passable(start) {
+        return filled;
+    }
+
+    let mut frontier = std::collections::VecDeque::from([start]);
+    filled.insert(start);
+
+    while let Some(tile) = frontier.pop_front() {
+        for neighbor in neighbors(tile, Connectivity::Four) {
+            if filled.len() >= max_tiles {
+                return filled;
+            }
+
+            if !filled.contains(&neighbor) && is_passable(neighbor) {
+                filled.insert(neighbor);
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+
+    filled
+}
+
 pub fn register_types(type_registry: Res<AppTypeRegistry>) {
     let mut type_registry_w = type_registry.write();
 
     type_registry_w.add_registration(TileStretch::get_type_registration());
+    type_registry_w.add_registration(TileRegion::get_type_registration());
+    type_registry_w.add_registration(Rounding::get_type_registration());
+    type_registry_w.add_registration(Connectivity::get_type_registration());
 }
 
 /// A trait for getting a tile location from a struct.
@@ -190,6 +481,21 @@ impl GetTileLocation for &GlobalTransform {
     }
 }
 
+// for entities whose GlobalTransform hasn't been propagated yet, ie. freshly spawned root entities
+impl GetTileLocation for Transform {
+    #[inline]
+    fn location(&self, tile_stretch: TileStretch) -> IVec3 {
+        tile_stretch.get_closest(self.translation)
+    }
+}
+
+impl GetTileLocation for &Transform {
+    #[inline]
+    fn location(&self, tile_stretch: TileStretch) -> IVec3 {
+        tile_stretch.get_closest(self.translation)
+    }
+}
+
 impl GetTileLocation for &Vec3 {
     #[inline]
     fn location(&self, tile_stretch: TileStretch) -> IVec3 {
@@ -224,6 +530,6 @@ impl std::ops::Mul<Vec3> for TileStretch {
 
     #[inline]
     fn mul(self, rhs: Vec3) -> Self::Output {
-        Vec3::new(rhs.x * self.0 as f32, rhs.y * self.1 as f32, rhs.z)
+        Vec3::new(rhs.x * f32::from(self.x), rhs.y * f32::from(self.y), rhs.z)
     }
 }
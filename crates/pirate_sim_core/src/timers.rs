@@ -0,0 +1,140 @@
+//! A pool of timers all ticked by a single system, for when many short-lived timers are needed
+//!
+//! Rather than each entity carrying its own [`Timer`] component, a timer is inserted into the
+//! [`TimerPool`], and only the returned [`TimerHandle`] is stored.
+
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::Reflect;
+use bevy_time::{Time, TimeSystem, Timer, TimerMode};
+
+/// A lightweight handle to a timer in a [`TimerPool`]
+///
+/// Handles are never reused, so a handle to a removed timer will not point to a newer one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct TimerHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    generation: u32,
+    timer: Option<Timer>,
+}
+
+/// Every pooled timer. See the [module docs](self)
+#[derive(Resource, Debug, Default)]
+pub struct TimerPool {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl TimerPool {
+    /// Start a new one-shot timer that finishes after `duration`
+    ///
+    /// # Panics
+    /// If more than [`u32::MAX`] timers are in the pool at once
+    pub fn insert(&mut self, duration: Duration) -> TimerHandle {
+        let timer = Timer::new(duration, TimerMode::Once);
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.timer = Some(timer);
+
+            return TimerHandle {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        let index = u32::try_from(self.slots.len()).expect("too many pooled timers");
+        self.slots.push(Slot {
+            generation: 0,
+            timer: Some(timer),
+        });
+
+        TimerHandle {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// Remove a timer from the pool, returning it if it still existed
+    pub fn remove(&mut self, handle: TimerHandle) -> Option<Timer> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        let timer = slot.timer.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+
+        Some(timer)
+    }
+
+    #[must_use]
+    pub fn get(&self, handle: TimerHandle) -> Option<&Timer> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .timer
+            .as_ref()
+    }
+
+    /// Whether the timer has finished. A removed timer counts as finished.
+    #[must_use]
+    pub fn finished(&self, handle: TimerHandle) -> bool {
+        match self.get(handle) {
+            Some(timer) => timer.finished(),
+            None => true,
+        }
+    }
+
+    /// Every timer that finished during the last tick
+    pub fn just_finished(&self) -> impl Iterator<Item = TimerHandle> + '_ {
+        self.iter()
+            .filter(|(_, timer)| timer.just_finished())
+            .map(|(handle, _)| handle)
+    }
+
+    /// Every timer in the pool, along with its handle
+    pub fn iter(&self) -> impl Iterator<Item = (TimerHandle, &Timer)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.timer.as_ref().map(|timer| {
+                (
+                    TimerHandle {
+                        index: index as u32,
+                        generation: slot.generation,
+                    },
+                    timer,
+                )
+            })
+        })
+    }
+
+    /// Advance every timer in the pool by `delta`
+    pub fn tick(&mut self, delta: Duration) {
+        for timer in self.slots.iter_mut().filter_map(|slot| slot.timer.as_mut()) {
+            timer.tick(delta);
+        }
+    }
+}
+
+pub fn tick_timer_pool(mut pool: ResMut<TimerPool>, time: Res<Time>) {
+    pool.tick(time.delta());
+}
+
+/// Sets up a [`TimerPool`] and ticks it at the start of each frame, just after [`Time`] updates
+pub struct TimerPoolPlugin;
+
+impl Plugin for TimerPoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimerPool>()
+            .add_systems(First, tick_timer_pool.after(TimeSystem));
+    }
+}
@@ -4,6 +4,27 @@ use bevy_ecs::{
 };
 use bevy_math::{BVec3, Vec3};
 
+/// The tolerance used when comparing floats in tilespace, eg. whether a cast ray has landed on a
+/// tile.
+///
+/// Tilespace values are whole numbers when on grid, so this only needs to absorb the error
+/// accumulated through a handful of float operations.
+pub const TOLERANCE: f32 = 1e-4;
+
+/// Whether `a` and `b` are within `tolerance` of each other. See [`TOLERANCE`]
+#[must_use]
+#[inline]
+pub fn approx_eq(a: f32, b: f32, tolerance: f32) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+/// [`approx_eq`] for every axis of a [`Vec3`]
+#[must_use]
+#[inline]
+pub fn approx_eq_vec3(a: Vec3, b: Vec3, tolerance: f32) -> bool {
+    (a - b).abs().cmple(Vec3::splat(tolerance)).all()
+}
+
 /// Lookup a newtype over [`Vec3`] or return [`Vec3::ZERO`]
 #[must_use]
 #[inline]
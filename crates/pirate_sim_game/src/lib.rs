@@ -0,0 +1,21 @@
+//! The game's own rendering of the tile world, and helpers for setting up scenes with it, shared
+//! by the main binary, examples, and tests
+//!
+//! [`tile_objects`] draws tiles with sprites from the spritesheet, [`sea`] floods everything below
+//! the sea level, and [`simple_scene`] sets up a minimal playable scene without needing everything
+//! the main `setup` spawns.
+
+#![warn(clippy::unwrap_used)]
+#![warn(clippy::perf, clippy::disallowed_types)] // performance warns
+#![warn(clippy::pedantic)]
+// most bevy systems violate these. Nothing I can do about it at the moment.
+#![allow(
+    clippy::type_complexity,
+    clippy::too_many_arguments,
+    clippy::needless_pass_by_value // TODO: separate out system functions from non-system 
+)]
+#![allow(clippy::cast_possible_truncation)]
+
+pub mod sea;
+pub mod simple_scene;
+pub mod tile_objects;
@@ -0,0 +1,144 @@
+//! The game's sea, filling everything below the [`SeaLevel`] with water
+//!
+//! See [`Plugin`]
+
+use bevy::prelude::*;
+
+use pirate_sim_physics::fluid::SeaLevel;
+
+/// The [`SeaLevel`] inserted by a default [`Plugin`], just below floors at z 0
+pub const DEFAULT_SEA_LEVEL: i32 = -1;
+
+/// Insert a [`SeaLevel`] at `starting_level` if there isn't one already. With the
+/// `developer-tools` feature, also registers the `set_sealevel <z>` console command.
+#[derive(Debug, Clone, Copy)]
+pub struct Plugin {
+    pub starting_level: i32,
+}
+
+impl Default for Plugin {
+    fn default() -> Self {
+        Self {
+            starting_level: DEFAULT_SEA_LEVEL,
+        }
+    }
+}
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<SeaLevel>() {
+            app.insert_resource(SeaLevel::new(self.starting_level));
+        }
+
+        #[cfg(feature = "developer-tools")]
+        app.add_systems(Startup, console::register_commands);
+    }
+}
+
+#[cfg(feature = "developer-tools")]
+mod console {
+    use std::collections::VecDeque;
+
+    use bevy::prelude::*;
+    use pirate_sim_console::{
+        registration::RegisterConsoleCommand, Output, PrintStringCommand, Token,
+    };
+    use pirate_sim_physics::fluid::SeaLevel;
+
+    pub(super) fn register_commands(mut commands: Commands) {
+        commands.add(RegisterConsoleCommand::new(
+            "set_sealevel".into(),
+            set_sealevel_command,
+        ));
+    }
+
+    fn set_sealevel_command(mut input: VecDeque<Token>, commands: &mut Commands) {
+        // set_sealevel <z>
+
+        if input.len() != 1 {
+            commands.add(PrintStringCommand(format!(
+                "Wrong amount of inputs. Expected 1, got {}",
+                input.len()
+            )));
+            return;
+        }
+
+        #[allow(clippy::unwrap_used)]
+        let z = match input.pop_front().unwrap().string.parse::<i32>() {
+            Ok(z) => z,
+            Err(e) => {
+                commands.add(PrintStringCommand(format!("Parsing error `{e}`")));
+                return;
+            }
+        };
+
+        commands.add(move |world: &mut World| {
+            let output = if let Some(mut sea_level) = world.get_resource_mut::<SeaLevel>() {
+                let from = sea_level.z();
+                sea_level.set(z);
+
+                format!("Sea level moved from {from} to {z}")
+            } else {
+                world.insert_resource(SeaLevel::new(z));
+
+                format!("Sea level set to {z}")
+            };
+
+            world.send_event(Output::String(output));
+            world.send_event(Output::End);
+        });
+    }
+
+    #[cfg(test)]
+    #[test]
+    fn set_sealevel_moves_the_sea() {
+        use bevy::ecs::system::CommandQueue;
+        use pirate_sim_console::parse;
+
+        let mut world = World::new();
+        world.init_resource::<Events<Output>>();
+
+        let set_sealevel = |world: &mut World, args: &[&str]| {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            set_sealevel_command(
+                parse(&args.join(" ")).expect("arguments parse"),
+                &mut commands,
+            );
+            queue.apply(world);
+
+            world.get_resource::<SeaLevel>().map(SeaLevel::z)
+        };
+
+        // there doesn't need to be a sea already
+        assert_eq!(set_sealevel(&mut world, &["2"]), Some(2));
+        assert_eq!(set_sealevel(&mut world, &["-4"]), Some(-4));
+
+        // bad input leaves it alone
+        assert_eq!(set_sealevel(&mut world, &["high"]), Some(-4));
+        assert_eq!(set_sealevel(&mut world, &[]), Some(-4));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plugin_keeps_an_existing_sea_level() {
+        let mut app = App::new();
+        app.add_plugins(Plugin::default());
+        assert_eq!(
+            app.world.get_resource::<SeaLevel>().map(SeaLevel::z),
+            Some(DEFAULT_SEA_LEVEL)
+        );
+
+        let mut app = App::new();
+        app.insert_resource(SeaLevel::new(4));
+        app.add_plugins(Plugin::default());
+        assert_eq!(
+            app.world.get_resource::<SeaLevel>().map(SeaLevel::z),
+            Some(4)
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! A minimal playable scene, for examples and tests that don't need everything the main `setup`
+//! spawns
+//!
+//! See [`SimpleSceneExt::add_simple_scene`]
+
+use bevy::prelude::*;
+
+use pirate_sim_core::tile_grid::{tiles_in, TileStretch};
+use pirate_sim_physics::Collider;
+
+use crate::tile_objects::{SpriteSheetHandle, TileCamera, TileObject};
+
+/// How many tiles wide and tall the floor of the simple scene is
+pub const SIMPLE_SCENE_SIZE: i32 = 8;
+
+pub trait SimpleSceneExt {
+    /// Insert a [`TileStretch`] if there isn't one already, and spawn a camera and a
+    /// [`SIMPLE_SCENE_SIZE`] square grid of floor tiles at z 0 on startup.
+    ///
+    /// The floor uses the [`SpriteSheetHandle`] if there is one, and otherwise an empty handle, so
+    /// no assets need to be loaded.
+    fn add_simple_scene(&mut self) -> &mut Self;
+}
+
+impl SimpleSceneExt for App {
+    fn add_simple_scene(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<TileStretch>() {
+            // the same size as tile_objects::SPRITE_SIZE
+            self.insert_resource(TileStretch::new(32, 32));
+        }
+
+        self.add_systems(Startup, spawn_simple_scene)
+    }
+}
+
+fn spawn_simple_scene(
+    mut commands: Commands,
+    tile_stretch: Res<TileStretch>,
+    spritesheet_handle: Option<Res<SpriteSheetHandle>>,
+) {
+    commands.spawn((
+        Camera2dBundle {
+            transform: Transform::from_xyz(0., 0., 2.),
+            ..default()
+        },
+        TileCamera(),
+    ));
+
+    let texture_atlas = spritesheet_handle.map_or_else(Handle::default, |h| h.0.clone());
+    let floor_sprite = TileObject::new(5, 6, 7);
+
+    for tile in tiles_in(
+        IVec3::ZERO,
+        IVec3::new(SIMPLE_SCENE_SIZE - 1, SIMPLE_SCENE_SIZE - 1, 0),
+    ) {
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_atlas.clone(),
+                sprite: TextureAtlasSprite::new(floor_sprite.main_layer_index),
+                transform: Transform::from_translation(tile_stretch.get_bevy(tile)),
+                ..default()
+            },
+            floor_sprite,
+            Name::new("Floor"),
+            Collider::floor(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pirate_sim_physics::collision::Constraints;
+
+    use super::*;
+
+    #[test]
+    fn simple_scene_spawns_camera_and_floor() {
+        let mut app = App::new();
+        app.add_simple_scene();
+        app.update();
+
+        assert!(app.world.contains_resource::<TileStretch>());
+
+        let cameras = app
+            .world
+            .query_filtered::<(), (With<Camera>, With<TileCamera>)>()
+            .iter(&app.world)
+            .count();
+        assert_eq!(cameras, 1);
+
+        let floors: Vec<_> = app
+            .world
+            .query::<&Collider>()
+            .iter(&app.world)
+            .map(|c| c.constraints)
+            .collect();
+        assert_eq!(
+            floors.len(),
+            usize::try_from(SIMPLE_SCENE_SIZE * SIMPLE_SCENE_SIZE).expect("size is positive")
+        );
+        assert!(floors.iter().all(|c| *c == Constraints::FLOOR));
+    }
+}
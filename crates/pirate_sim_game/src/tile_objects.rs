@@ -0,0 +1,789 @@
+//! Functions and systems for manipulating and updating objects using tiled sprites from the
+//! spritesheet
+//!
+//! In the future, setup spritesheet, tilestretch, etc and process spritesheet images
+
+// still in heavy development
+
+use bevy::{
+    prelude::*,
+    reflect::GetTypeRegistration,
+    render::{Extract, ExtractSchedule, RenderApp},
+    sprite::{ExtractedSprites, SpriteSystem},
+};
+
+use pirate_sim_core::{thiserror, tile_grid::TileStretch};
+use pirate_sim_physics::{movement::Facing, PhysicsSet};
+
+#[derive(Resource, Deref, DerefMut, Reflect)]
+pub struct SpriteSheetHandle(pub Handle<TextureAtlas>);
+
+/// The size of the main spritesheet, in sprites
+pub const SPRITESHEET_COLUMNS: usize = 16;
+pub const SPRITESHEET_ROWS: usize = 16;
+
+/// The size of a single sprite in the main spritesheet, in pixels. [`TileStretch`] should be taken
+/// from the loaded atlas with [`StretchFromAtlas::from_atlas`] rather than from this.
+pub const SPRITE_SIZE: Vec2 = Vec2::new(32., 32.);
+
+/// Why a [`TileStretch`] couldn't be read from a [`TextureAtlas`]
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
+pub enum FromAtlasError {
+    /// the atlas has no sprites to measure
+    #[error("texture atlas has no sprites")]
+    Empty,
+    /// two of the atlas's sprites have different sizes
+    #[error("texture atlas has sprites of size {0} and {1}")]
+    NotUniform(Vec2, Vec2),
+    /// a sprite is larger than a [`TileStretch`] can hold, or isn't a whole number of pixels
+    #[error("sprite size {0} can't be a tile stretch")]
+    InvalidSize(Vec2),
+}
+
+/// Reading a [`TileStretch`] straight from a spritesheet, so that the two can't drift apart
+pub trait StretchFromAtlas: Sized {
+    /// The size of every sprite in `atlas`, which must all be the same
+    ///
+    /// # Errors
+    /// See [`FromAtlasError`]
+    fn from_atlas(atlas: &TextureAtlas) -> Result<Self, FromAtlasError>;
+}
+
+impl StretchFromAtlas for TileStretch {
+    fn from_atlas(atlas: &TextureAtlas) -> Result<Self, FromAtlasError> {
+        let mut sizes = atlas.textures.iter().map(Rect::size);
+
+        let size = sizes.next().ok_or(FromAtlasError::Empty)?;
+
+        if let Some(other) = sizes.find(|other| *other != size) {
+            return Err(FromAtlasError::NotUniform(size, other));
+        }
+
+        let fits = |axis: f32| axis.fract() == 0. && (1. ..=f32::from(u8::MAX)).contains(&axis);
+
+        if !(fits(size.x) && fits(size.y)) {
+            return Err(FromAtlasError::InvalidSize(size));
+        }
+
+        #[allow(clippy::cast_sign_loss)]
+        Ok(Self::new(size.x as u8, size.y as u8))
+    }
+}
+
+#[derive(Clone, Copy, Component, Reflect, Debug)]
+pub struct TileCamera();
+
+#[derive(Clone, Copy, Component, Reflect)]
+pub struct ConnectingWall();
+
+/// Marks that an entity should be managed as a rendered object, as well as
+/// providing information about how it should be rendered.
+///
+/// `main_layer`,`one_up_index`, and `two_up_index` will decide which sprite to use when the sprite is on camera,
+/// based on how far up the camera is from them.
+#[derive(Component, Clone, Copy, Reflect, Debug)]
+pub struct TileObject {
+    pub main_layer_index: usize,
+    pub one_up_index: usize,
+    pub two_up_index: usize,
+}
+
+/// Where the sprites for the layers above a [`TileObject`]'s main layer sit in the spritesheet,
+/// relative to the main sprite. See [`TileObject::from_layout`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerStride {
+    /// Each layer up is the next sprite in the same row, eg. 202, 203, 204
+    NextInRow,
+    /// Each layer up is in the same column, one row further down the sheet
+    RowBelow,
+    /// Each layer up is in the same column, one row further up the sheet
+    RowAbove,
+}
+
+impl LayerStride {
+    /// The index of the sprite one layer up from `index`
+    fn next(self, index: usize, columns: usize) -> Option<usize> {
+        match self {
+            Self::NextInRow => index.checked_add(1),
+            Self::RowBelow => index.checked_add(columns),
+            Self::RowAbove => index.checked_sub(columns),
+        }
+    }
+}
+
+// TODO: encapsulate so must be instantiated through TileObjectBundle
+impl TileObject {
+    #[must_use]
+    pub fn new(main: usize, one_up: usize, two_up: usize) -> Self {
+        Self {
+            main_layer_index: main,
+            one_up_index: one_up,
+            two_up_index: two_up,
+        }
+    }
+
+    /// Work out the one up and two up indices from just the main index, for a spritesheet that is
+    /// `columns` sprites wide.
+    ///
+    /// Returns `None` if the layers above would fall off the start of the spritesheet.
+    #[must_use]
+    pub fn from_layout(main: usize, stride: LayerStride, columns: usize) -> Option<Self> {
+        let one_up = stride.next(main, columns)?;
+        let two_up = stride.next(one_up, columns)?;
+
+        Some(Self::new(main, one_up, two_up))
+    }
+}
+
+/// Eight sprites for a [`TileObject`]'s main layer, one for each direction its [`Facing`] can
+/// point in. The layers above are left as they are.
+///
+/// `indices` go counter-clockwise starting from east: east, north-east, north, north-west, west,
+/// south-west, south, and south-east.
+#[derive(Component, Clone, Copy, Reflect, Debug)]
+pub struct DirectionalSprite {
+    pub indices: [usize; 8],
+}
+
+impl DirectionalSprite {
+    /// The sprite index for facing along `facing`. Only the signs of x and y are used, and facing
+    /// nowhere is treated as facing south.
+    #[must_use]
+    pub fn index_for(&self, facing: IVec3) -> usize {
+        let direction = match (facing.x.signum(), facing.y.signum()) {
+            (1, 0) => 0,
+            (1, 1) => 1,
+            (0, 1) => 2,
+            (-1, 1) => 3,
+            (-1, 0) => 4,
+            (-1, -1) => 5,
+            (1, -1) => 7,
+            _ => 6,
+        };
+
+        self.indices[direction]
+    }
+}
+
+/// Modulates an entity's sprite color, eg. to flash on damage or highlight a selection.
+///
+/// With a `timer`, the tint is removed and the sprite goes back to white once it finishes.
+/// Otherwise the tint lasts until this component is removed.
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct SpriteTint {
+    pub color: Color,
+    pub timer: Option<Timer>,
+}
+
+impl SpriteTint {
+    /// A tint lasting until it is removed
+    #[must_use]
+    pub fn new(color: Color) -> Self {
+        Self { color, timer: None }
+    }
+
+    /// A tint lasting `seconds`
+    #[must_use]
+    pub fn timed(color: Color, seconds: f32) -> Self {
+        Self {
+            color,
+            timer: Some(Timer::from_seconds(seconds, TimerMode::Once)),
+        }
+    }
+}
+
+pub fn apply_sprite_tints(
+    mut commands: Commands,
+    mut tinted_q: Query<(Entity, &mut SpriteTint, &mut TextureAtlasSprite)>,
+    mut untinted_q: Query<&mut TextureAtlasSprite, Without<SpriteTint>>,
+    mut removed: RemovedComponents<SpriteTint>,
+    time: Res<Time>,
+) {
+    for (entity, mut tint, mut sprite) in &mut tinted_q {
+        let expired = tint
+            .timer
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).finished());
+
+        if expired {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<SpriteTint>();
+        } else if sprite.color != tint.color {
+            sprite.color = tint.color;
+        }
+    }
+
+    // tints removed by hand rather than by expiring
+    for entity in removed.iter() {
+        if let Ok(mut sprite) = untinted_q.get_mut(entity) {
+            sprite.color = Color::WHITE;
+        }
+    }
+}
+
+/// Nudges where an entity's sprite is drawn along z without moving the entity, to fine tune which
+/// of two sprites on the same tile is drawn in front. Higher is further in front.
+///
+/// The bias is only added to the sprite's copy of the transform extracted for rendering, so the
+/// entity's [`Transform`], [`GlobalTransform`] and tile are left alone. Keep it under half a tile
+/// either way, or the sprite will be drawn as if it were on another z level.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct DepthBias(pub f32);
+
+/// Add each [`DepthBias`] onto the transform its sprite was extracted with
+fn extract_depth_bias(
+    mut extracted_sprites: ResMut<ExtractedSprites>,
+    biased_q: Extract<Query<&DepthBias>>,
+) {
+    for sprite in &mut extracted_sprites.sprites {
+        let Ok(bias) = biased_q.get(sprite.entity) else {
+            continue;
+        };
+
+        let mut affine = sprite.transform.affine();
+        affine.translation.z += bias.0;
+        sprite.transform = GlobalTransform::from(affine);
+    }
+}
+
+pub fn register_types(type_registry: Res<AppTypeRegistry>) {
+    let mut type_registry_w = type_registry.write();
+
+    type_registry_w.add_registration(ConnectingWall::get_type_registration());
+    type_registry_w.add_registration(SpriteSheetHandle::get_type_registration());
+    type_registry_w.add_registration(TileCamera::get_type_registration());
+    type_registry_w.add_registration(TileObject::get_type_registration());
+    type_registry_w.add_registration(DirectionalSprite::get_type_registration());
+    type_registry_w.add_registration(SpriteTint::get_type_registration());
+    type_registry_w.add_registration(DepthBias::get_type_registration());
+}
+
+/// a 2d bounding box used to represent a cameras viewport
+struct BB2 {
+    top_left: Vec2,
+    bottom_right: Vec2,
+}
+impl BB2 {
+    ///  inclusively check if point is inside self
+    pub fn inside(&self, point: Vec3) -> bool {
+        // self.min.z <= point.z
+        //     && point.z <= self.max.z
+        self.top_left.x <= point.x
+            && point.x <= self.bottom_right.x
+            && self.bottom_right.y <= point.y
+            && point.y <= self.top_left.y
+    }
+    pub fn new(bottom_right: Vec2, top_left: Vec2) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+}
+
+/// Show each [`TileObject`] with the sprite for how far it is below the nearest [`TileCamera`]
+/// looking at it, hiding any that no camera can see
+///
+/// # Panics
+/// If a [`TileCamera`] has no [`GlobalTransform`]
+pub fn update_tile_sprites(
+    tile_camera_q: Query<Entity, (With<TileCamera>, With<Camera>)>,
+    camera_q: Query<Ref<Camera>>,
+    transform_q: Query<&GlobalTransform>,
+    mut tile_object_q: Query<(
+        Option<&mut TextureAtlasSprite>,
+        Option<&mut Visibility>,
+        Ref<GlobalTransform>,
+        Ref<TileObject>,
+        Option<(&DirectionalSprite, Ref<Facing>)>,
+    )>,
+    tile_stretch: Res<TileStretch>,
+) {
+    if !(tile_object_q
+        .iter()
+        .any(|t| t.2.is_changed() || t.3.is_changed() || t.4.is_some_and(|(_, f)| f.is_changed()))
+        || camera_q.iter().any(|c| c.is_changed()))
+    {
+        trace!("No tile sprite changes/camera changes to update");
+        return;
+    }
+
+    trace!("Updating tile sprites");
+
+    // although this may look like a dictionary, as we're going to be iterating through it for each
+    // tile entity, we want quick iteration and not quick indexing.
+    let bounds: Vec<(BB2, f32)> = tile_camera_q
+        .iter()
+        .filter_map(|camera_entity| {
+            // SAFETY: we filter camera_q on With<Camera>, then get e from that q
+            let camera = unsafe { camera_q.get(camera_entity).unwrap_unchecked() };
+            let camera_transform = transform_q
+                .get(camera_entity)
+                .expect("Camera with no transform??");
+
+            let viewport = camera.logical_viewport_rect()?;
+
+            // top left corner->bottom right corner of camera in world space.
+            let bottom_right = camera.viewport_to_world_2d(camera_transform, viewport.min)?;
+            let top_left = camera.viewport_to_world_2d(camera_transform, viewport.max)?;
+
+            debug!("{} -> {}", bottom_right, top_left);
+
+            // this should put the point into the middle of the tilespace grid based on
+            // tilestretch?
+            //
+            // the tilespace grid functions such that each grid centers on a multiple of
+            // tilestretch.{x,y} on the {x,y} axis, and is the same size.
+            let round_to_tile_space = |to_round: Vec2| -> Vec2 {
+                let x = to_round.x + f32::from(tile_stretch.x) * to_round.x.signum();
+                let y = to_round.y + f32::from(tile_stretch.y) * to_round.y.signum();
+
+                Vec2::new(x, y)
+            };
+
+            // align start and end to a grid, so that it will align with entity origins
+            let bottom_right = round_to_tile_space(bottom_right);
+            let top_left = round_to_tile_space(top_left);
+
+            Some((
+                BB2::new(top_left, bottom_right),
+                camera_transform.translation().z,
+            ))
+        })
+        .collect();
+
+    apply_entity_from_bounds(&bounds, &mut tile_object_q);
+}
+
+// because we're parallel iterating over everything as essentially its own entity, and that's all we
+// do in this function, have all queries in one parameter
+//
+// this function is split out in case bevy system piping ever becomes beneficially performant, and
+// useful
+fn apply_entity_from_bounds(
+    all_bounds: &[(BB2, f32)],
+    tile_object_q: &mut Query<(
+        Option<&mut TextureAtlasSprite>,
+        Option<&mut Visibility>,
+        Ref<GlobalTransform>,
+        Ref<TileObject>,
+        Option<(&DirectionalSprite, Ref<Facing>)>,
+    )>,
+) {
+    // check each tile object
+    tile_object_q.par_iter_mut().for_each_mut(
+        |(option_sprite, option_visibility, transform, tile_object, directional)| {
+            // ensure it has a sprite and a visibility associated
+            let Some(mut sprite) = option_sprite else {
+                warn!("TileObject with no sprite!");
+                return;
+            };
+
+            let Some(mut visibility) = option_visibility  else {
+                warn!("TileObject with no visibility");
+                return;
+            };
+
+            // the direction only decides the main layer, the layers above stay the same
+            let tile_object = match directional {
+                Some((directional, facing)) => TileObject {
+                    main_layer_index: directional.index_for(facing.0),
+                    ..*tile_object
+                },
+                None => *tile_object,
+            };
+
+            match sprite_index_for(all_bounds, transform.translation(), &tile_object) {
+                Some(index) => {
+                    *visibility = Visibility::Inherited;
+                    sprite.index = index;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
+        },
+    );
+}
+
+/// The sprite index a tile object at `translation` should use, or `None` if it shouldn't be
+/// rendered at all.
+///
+/// Sprites are shared between every camera, so with multiple cameras an object is drawn for the
+/// nearest camera at or above it (out of the cameras whose bounds contain it). That camera picks
+/// the layer based on how far above the object it is, and the object is hidden if that is more
+/// than two levels. Cameras below an object never see it.
+fn sprite_index_for(
+    all_bounds: &[(BB2, f32)],
+    translation: Vec3,
+    tile_object: &TileObject,
+) -> Option<usize> {
+    let current_z = translation.z as isize;
+
+    let nearest_z = all_bounds
+        .iter()
+        .filter(|(bound, _)| {
+            trace!(
+                "checking if {} is inside {}-{}",
+                translation,
+                bound.top_left,
+                bound.bottom_right
+            );
+
+            bound.inside(translation)
+        })
+        .map(|(_, z)| *z as isize)
+        .filter(|z| *z >= current_z)
+        .min()?;
+
+    match nearest_z - current_z {
+        0 => Some(tile_object.main_layer_index),
+        1 => Some(tile_object.one_up_index),
+        2 => Some(tile_object.two_up_index),
+        _ => None, // too far down
+    }
+}
+
+pub struct Plugin;
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, register_types).add_systems(
+            Update,
+            (
+                update_tile_sprites.in_set(PhysicsSet::Completed),
+                apply_sprite_tints,
+            ),
+        );
+
+        // without a renderer nothing is drawn, so there's nothing to bias
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                ExtractSchedule,
+                extract_depth_bias.after(SpriteSystem::ExtractSprites),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn multiple_cameras_pick_nearest_layer() {
+    let tile_object = TileObject::new(1, 2, 3);
+    let at_origin = Vec3::ZERO;
+
+    let sees_origin = |z: f32| (BB2::new(Vec2::new(32., -32.), Vec2::new(-32., 32.)), z);
+    let elsewhere = |z: f32| (BB2::new(Vec2::new(132., 68.), Vec2::new(68., 132.)), z);
+
+    // the nearest camera above wins, no matter the order
+    assert_eq!(
+        sprite_index_for(&[sees_origin(3.), sees_origin(1.)], at_origin, &tile_object),
+        Some(2)
+    );
+    assert_eq!(
+        sprite_index_for(&[sees_origin(0.), sees_origin(2.)], at_origin, &tile_object),
+        Some(1)
+    );
+
+    // only cameras that can see the object count
+    assert_eq!(
+        sprite_index_for(&[elsewhere(1.), sees_origin(2.)], at_origin, &tile_object),
+        Some(3)
+    );
+    assert_eq!(
+        sprite_index_for(&[sees_origin(1.), elsewhere(2.)], at_origin, &tile_object),
+        Some(2)
+    );
+
+    // a camera below doesn't hide an object from a camera above
+    assert_eq!(
+        sprite_index_for(
+            &[sees_origin(-1.), sees_origin(1.)],
+            at_origin,
+            &tile_object
+        ),
+        Some(2)
+    );
+
+    // too far away, or only seen from below
+    assert_eq!(
+        sprite_index_for(&[sees_origin(3.)], at_origin, &tile_object),
+        None
+    );
+    assert_eq!(
+        sprite_index_for(&[sees_origin(-1.)], at_origin, &tile_object),
+        None
+    );
+    assert_eq!(
+        sprite_index_for(&[elsewhere(1.)], at_origin, &tile_object),
+        None
+    );
+}
+
+#[cfg(test)]
+#[test]
+#[allow(clippy::unwrap_used)]
+fn directional_sprite_follows_facing() {
+    use bevy::time::TimeUpdateStrategy;
+    use pirate_sim_core::test_utils::DefaultTestPlugin;
+    use pirate_sim_physics::{movement::MovementBundle, MovementGoal, PhysicsPlugin};
+
+    let directional = DirectionalSprite {
+        indices: [10, 11, 12, 13, 14, 15, 16, 17],
+    };
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins(PhysicsPlugin::default());
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+
+    let walker = app
+        .world
+        .spawn((
+            Name::new("Walker"),
+            MovementBundle::default(),
+            directional,
+            TransformBundle::default(),
+        ))
+        .id();
+
+    // standing still faces south
+    app.update();
+    assert_eq!(
+        directional.index_for(app.world.get::<Facing>(walker).unwrap().0),
+        16
+    );
+
+    let directions = [
+        (Vec3::X, 10),
+        (Vec3::X + Vec3::Y, 11),
+        (Vec3::Y, 12),
+        (Vec3::NEG_X + Vec3::Y, 13),
+        (Vec3::NEG_X, 14),
+        (Vec3::NEG_X + Vec3::NEG_Y, 15),
+        (Vec3::NEG_Y, 16),
+        (Vec3::X + Vec3::NEG_Y, 17),
+    ];
+
+    for (direction, expected) in directions {
+        app.world.entity_mut(walker).insert(MovementGoal(direction));
+        app.update();
+
+        let facing = app.world.get::<Facing>(walker).unwrap().0;
+        assert_eq!(
+            directional.index_for(facing),
+            expected,
+            "wrong sprite walking along {direction}"
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn layer_indices_follow_stride() {
+    let indices = |tile_object: Option<TileObject>| {
+        tile_object.map(|t| (t.main_layer_index, t.one_up_index, t.two_up_index))
+    };
+
+    assert_eq!(
+        indices(TileObject::from_layout(202, LayerStride::NextInRow, 16)),
+        Some((202, 203, 204))
+    );
+    assert_eq!(
+        indices(TileObject::from_layout(5, LayerStride::RowBelow, 16)),
+        Some((5, 21, 37))
+    );
+    assert_eq!(
+        indices(TileObject::from_layout(40, LayerStride::RowAbove, 16)),
+        Some((40, 24, 8))
+    );
+
+    // there is nothing two rows above the second row
+    assert_eq!(
+        indices(TileObject::from_layout(20, LayerStride::RowAbove, 16)),
+        None
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn tile_stretch_read_from_atlas() {
+    let grid = TextureAtlas::from_grid(Handle::default(), Vec2::new(16., 32.), 4, 2, None, None);
+
+    assert_eq!(TileStretch::from_atlas(&grid), Ok(TileStretch::new(16, 32)));
+
+    let mut uneven = TextureAtlas::new_empty(Handle::default(), Vec2::new(64., 64.));
+    uneven.add_texture(Rect::new(0., 0., 16., 16.));
+    uneven.add_texture(Rect::new(16., 0., 48., 16.));
+
+    assert_eq!(
+        TileStretch::from_atlas(&uneven),
+        Err(FromAtlasError::NotUniform(
+            Vec2::new(16., 16.),
+            Vec2::new(32., 16.)
+        ))
+    );
+
+    let empty = TextureAtlas::new_empty(Handle::default(), Vec2::new(64., 64.));
+    assert_eq!(TileStretch::from_atlas(&empty), Err(FromAtlasError::Empty));
+}
+
+#[cfg(test)]
+#[test]
+fn timed_tint_reverts_to_white() {
+    use bevy::utils::{Duration, Instant};
+
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .add_systems(Update, apply_sprite_tints);
+
+    let tinted = app
+        .world
+        .spawn((
+            TextureAtlasSprite::default(),
+            SpriteTint::timed(Color::RED, 0.5),
+        ))
+        .id();
+
+    let start = Instant::now();
+    let advance_to = |app: &mut App, seconds: f32| {
+        app.world
+            .resource_mut::<Time>()
+            .update_with_instant(start + Duration::from_secs_f32(seconds));
+        app.update();
+    };
+    let color = |app: &App| app.world.get::<TextureAtlasSprite>(tinted).map(|s| s.color);
+
+    advance_to(&mut app, 0.);
+    assert_eq!(color(&app), Some(Color::RED));
+
+    advance_to(&mut app, 0.3);
+    assert_eq!(color(&app), Some(Color::RED));
+
+    advance_to(&mut app, 0.6);
+    assert_eq!(color(&app), Some(Color::WHITE));
+    assert!(app.world.get::<SpriteTint>(tinted).is_none());
+}
+
+#[cfg(test)]
+#[test]
+#[allow(clippy::unwrap_used)]
+fn depth_bias_orders_sprites_on_a_tile() {
+    use bevy::{render::MainWorld, sprite::ExtractedSprite};
+
+    let mut render_world = World::new();
+    render_world.init_resource::<MainWorld>();
+    render_world.init_resource::<ExtractedSprites>();
+
+    let mut extract = Schedule::default();
+    extract.add_systems(extract_depth_bias);
+
+    let transform = Transform::from_xyz(32., 0., 1.);
+    let mut spawn = |bias: f32| {
+        render_world
+            .resource_mut::<MainWorld>()
+            .spawn((transform, GlobalTransform::from(transform), DepthBias(bias)))
+            .id()
+    };
+    let rug = spawn(-0.1);
+    let table = spawn(0.2);
+
+    // extract both as the sprite extraction would, then bias them
+    let mut extract_z = |render_world: &mut World, entity| {
+        render_world.resource_mut::<ExtractedSprites>().sprites = vec![ExtractedSprite {
+            entity,
+            transform: GlobalTransform::from(transform),
+            color: Color::WHITE,
+            rect: None,
+            custom_size: None,
+            image_handle_id: Handle::<Image>::default().id(),
+            flip_x: false,
+            flip_y: false,
+            anchor: Vec2::ZERO,
+        }];
+        extract.run(render_world);
+
+        render_world.resource::<ExtractedSprites>().sprites[0]
+            .transform
+            .translation()
+            .z
+    };
+
+    assert!((extract_z(&mut render_world, rug) - 0.9).abs() < 1e-5);
+    assert!((extract_z(&mut render_world, table) - 1.2).abs() < 1e-5);
+
+    // changing the bias swaps the order
+    render_world
+        .resource_mut::<MainWorld>()
+        .get_mut::<DepthBias>(rug)
+        .unwrap()
+        .0 = 0.3;
+    assert!(extract_z(&mut render_world, rug) > extract_z(&mut render_world, table));
+
+    // the entity itself never moves
+    let main_world = render_world.resource::<MainWorld>();
+    for entity in [rug, table] {
+        assert_eq!(main_world.get::<Transform>(entity), Some(&transform));
+        assert_eq!(
+            main_world.get::<GlobalTransform>(entity),
+            Some(&GlobalTransform::from(transform))
+        );
+    }
+
+    // and is drawn where it is once unbiased
+    render_world
+        .resource_mut::<MainWorld>()
+        .entity_mut(rug)
+        .remove::<DepthBias>();
+    assert!((extract_z(&mut render_world, rug) - 1.).abs() < 1e-5);
+}
+
+#[cfg(test)]
+#[test]
+#[allow(clippy::unwrap_used)]
+fn depth_biased_collider_blocks_its_own_tile() {
+    use bevy::time::TimeUpdateStrategy;
+    use pirate_sim_core::{test_utils::DefaultTestPlugin, tile_grid::GetTileLocation};
+    use pirate_sim_physics::{movement::MovementBundle, Collider, MovementGoal, PhysicsPlugin};
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins((PhysicsPlugin::default(), Plugin));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1. / 60.),
+    ));
+
+    let tile_stretch = *app.world.resource::<TileStretch>();
+
+    let walker = app
+        .world
+        .spawn((
+            Name::new("Walker"),
+            MovementBundle::default(),
+            Collider::entity(),
+            MovementGoal(Vec3::X * 10.),
+            TransformBundle::default(),
+        ))
+        .id();
+    // drawn under anything else on its tile, but still on it
+    app.world.spawn((
+        Name::new("Wall"),
+        Collider::wall(),
+        DepthBias(-0.4),
+        TransformBundle::from_transform(Transform::from_translation(
+            tile_stretch.get_bevy(IVec3::new(2, 0, 0)),
+        )),
+    ));
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let location = app
+        .world
+        .get::<GlobalTransform>(walker)
+        .unwrap()
+        .location(tile_stretch);
+    assert_eq!(location, IVec3::new(1, 0, 0));
+}
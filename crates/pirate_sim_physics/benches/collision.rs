@@ -0,0 +1,61 @@
+//! How long collision takes as the number of colliders grows. Run with `cargo bench`
+//!
+//! Each row is a mover walking a tile per update into its own wall, as in the
+//! `many_colliders_all_stop_at_their_walls` test. With colliders looked up by tile, the time per
+//! update should grow about linearly with the number of rows.
+
+use std::time::{Duration, Instant};
+
+use bevy_app::prelude::*;
+use bevy_core::Name;
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+
+use pirate_sim_core::{goals::MovementGoal, test_utils::DefaultTestPlugin};
+use pirate_sim_physics::{movement::MovementBundle, Collider, PhysicsPlugin};
+
+const UPDATES: u32 = 20;
+
+fn time_updates(rows: u16) -> Duration {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultTestPlugin::default());
+    app.add_plugins(PhysicsPlugin {
+        delta_override: Some(1. / 60.),
+        ..Default::default()
+    });
+
+    for y in 0..rows {
+        app.world.spawn((
+            Name::new("Wall"),
+            Collider::wall(),
+            TransformBundle::from_transform(Transform::from_xyz(10., f32::from(y), 0.)),
+        ));
+        app.world.spawn((
+            Name::new("Mover"),
+            MovementBundle::default(),
+            Collider::entity(),
+            MovementGoal(Vec3::X * 60.),
+            TransformBundle::from_transform(Transform::from_xyz(0., f32::from(y), 0.)),
+        ));
+    }
+
+    // the first update also spawns and propagates everything, so leave it out
+    app.update();
+
+    let start = Instant::now();
+    for _ in 1..UPDATES {
+        app.update();
+    }
+    start.elapsed() / (UPDATES - 1)
+}
+
+fn main() {
+    for rows in [125, 250, 500, 1000] {
+        println!(
+            "{} colliders: {:?} per update",
+            u32::from(rows) * 2,
+            time_updates(rows)
+        );
+    }
+}
@@ -9,36 +9,147 @@
 //! Collisions however, are any time when two colliders will overlap, which does not always
 //! necessitate interference from the physics systems
 //!
+//! When two colliders move into the same empty tile in the same update, only one of them gets it,
+//! and the other is blocked as if the tile were already taken. The one with the higher [`Weight`]
+//! wins, treating no weight as 0, and if the weights are equal the one with the lower
+//! [`Entity::index`] wins. See [`wins_contested_tile`].
+//!
+//! A collider that can be moved along the axis something runs into it on, according to its
+//! [`Constraints::move_along`], is pushed instead of blocking. The pusher's velocity and
+//! [`Ticker`] along that axis are handed over to it, so that it steps out of the way in the same
+//! update. Only a single collider is ever pushed, so anything solid behind it, or it already
+//! moving, stops the push and blocks as usual.
+//!
+//! Colliders whose [`CollisionLayers`] exclude each other never interact at all.
+//!
+//! A collider with [`Restitution`] bounces off of whatever blocks it, rather than just stopping.
+//!
+//! Sensors send a [`TriggerEnter`] when another collider starts sharing their tile, and a
+//! [`TriggerExit`] when it stops.
+//!
 //! This module is probably rife with opportunities for performance improvements.
 
 use bevy_app::prelude::*;
 use bevy_core::Name;
-use bevy_derive::Deref;
-use bevy_ecs::prelude::*;
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{prelude::*, system::SystemParam};
 use bevy_log::prelude::*;
 use bevy_math::prelude::*;
 use bevy_reflect::prelude::*;
 use bevy_time::Time;
-use bevy_transform::prelude::GlobalTransform;
+use bevy_transform::{
+    prelude::{GlobalTransform, Transform},
+    TransformSystem,
+};
+use bevy_utils::{HashMap, HashSet};
 
 use pirate_sim_core::{utils::bvec_to_mask, PhysicsSet};
 
-use crate::tile_cast;
+use crate::{tile_cast, Weight};
 
 use super::{
-    movement::Ticker,
-    tile_cast::tile_cast,
+    movement::{PhysicsFrozen, Ticker},
+    tile_cast::tile_cast_traced,
     velocity::{RelativeVelocity, TotalVelocity},
 };
 
-use pirate_sim_core::tile_grid::{GetTileLocation, TileStretch};
+use pirate_sim_core::tile_grid::{tiles_in, GetTileLocation, TileStretch};
 use pirate_sim_core::utils;
 
+/// Every tile each collider takes up, both where it is predicted to be once this update's movement
+/// is done and where it currently is.
+///
+/// The map is rebuilt at the start of [`PhysicsSet::Collision`] each update, so it is only valid
+/// after that has run. Its current tiles are from before that update's
+/// [`PhysicsSet::Movement`], so anything that moved since will be on a predicted tile instead.
+///
+/// Dereferences to the predicted tiles, as `(tile, entity, constraints)`, in the same order every
+/// time for the same colliders. Looking up a single tile is done through a hashmap instead, so
+/// prefer [`Self::colliders_at`] over searching through every entry.
 #[derive(Resource, Deref, Debug, Default, Reflect)]
-pub struct CollisionMap(Vec<(IVec3, Entity, Constraints)>);
+pub struct CollisionMap {
+    #[deref]
+    predicted: Vec<(IVec3, Entity, Constraints)>,
+    // indices into `predicted` of the colliders on each tile
+    predicted_by_tile: HashMap<IVec3, Vec<usize>>,
+    current_by_tile: HashMap<IVec3, Vec<Entity>>,
+    // the physics delta time the predictions were made with
+    delta_seconds: f32,
+}
+
+impl CollisionMap {
+    /// Remove every entry. The map is rebuilt each update, so this only matters until then
+    pub fn clear(&mut self) {
+        self.predicted.clear();
+        self.predicted_by_tile.clear();
+        self.current_by_tile.clear();
+    }
+
+    /// Every entry predicted to be on `tile` once this update's movement is done
+    pub fn colliders_at(
+        &self,
+        tile: IVec3,
+    ) -> impl Iterator<Item = &(IVec3, Entity, Constraints)> + '_ {
+        self.predicted_by_tile
+            .get(&tile)
+            .into_iter()
+            .flatten()
+            .map(|index| &self.predicted[*index])
+    }
+
+    /// [`Self::colliders_at`] every tile in the box between `min` and `max`, inclusive
+    fn colliders_in(
+        &self,
+        min: IVec3,
+        max: IVec3,
+    ) -> impl Iterator<Item = &(IVec3, Entity, Constraints)> + '_ {
+        tiles_in(min, max).flat_map(|tile| self.colliders_at(tile))
+    }
+
+    /// Move every tile of `entity` along with it from being predicted on `from` to `to`, keeping
+    /// its place in the order of entries
+    fn move_predicted(&mut self, entity: Entity, from: IVec3, to: IVec3) {
+        // a collider's entries are next to each other, starting with the one its transform is on
+        let Some(first) = self
+            .predicted_by_tile
+            .get(&from)
+            .and_then(|indices| indices.iter().find(|i| self.predicted[**i].1 == entity))
+            .copied()
+        else {
+            return;
+        };
+
+        for index in first..self.predicted.len() {
+            let (tile, other, _) = &mut self.predicted[index];
+            if *other != entity {
+                break;
+            }
+
+            if let Some(indices) = self.predicted_by_tile.get_mut(tile) {
+                indices.retain(|i| *i != index);
+            }
+            *tile += to - from;
+            self.predicted_by_tile.entry(*tile).or_default().push(index);
+        }
+    }
+
+    /// Every collider predicted to be on `tile` once this update's movement is done
+    pub fn entities_at(&self, tile: IVec3) -> impl Iterator<Item = Entity> + '_ {
+        self.colliders_at(tile).map(|(_, entity, _)| *entity)
+    }
+
+    /// Every collider that was on `tile` when the map was built, see [`CollisionMap`]
+    pub fn entities_currently_at(&self, tile: IVec3) -> impl Iterator<Item = Entity> + '_ {
+        self.current_by_tile
+            .get(&tile)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+}
 
 /// constraints put onto a collider and its collisions
-#[derive(Debug, Clone, Copy, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 pub struct Constraints {
     /// which axes it is "solid"  along, and thus will cause a collision conflict
     ///
@@ -47,8 +158,13 @@ pub struct Constraints {
     pub neg_solid_planes: BVec3,
     /// Which axes it can be pushed along in order to resolve collision
     ///
-    /// This is currently ignored
+    /// Something running into this collider along one of these axes pushes it out of the way
+    /// instead of being stopped, see the [module docs](self). A collider's own impulses are also
+    /// only applied along these axes.
     pub move_along: BVec3,
+    /// Whether entities with [`Passthrough`] set drop through this, eg. for platforms that can be
+    /// jumped up through and dropped down from. See [`Self::ONE_WAY`]
+    pub one_way: bool,
 }
 
 impl Constraints {
@@ -56,6 +172,7 @@ impl Constraints {
         pos_solid_planes: BVec3::TRUE,
         neg_solid_planes: BVec3::TRUE,
         move_along: BVec3::FALSE,
+        one_way: false,
     };
     pub const FLOOR: Self = Self {
         pos_solid_planes: BVec3 {
@@ -65,6 +182,7 @@ impl Constraints {
         },
         neg_solid_planes: BVec3::FALSE,
         move_along: BVec3::FALSE,
+        one_way: false,
     };
     pub const ENTITY: Self = Self {
         pos_solid_planes: BVec3 {
@@ -79,39 +197,123 @@ impl Constraints {
         },
 
         move_along: BVec3::TRUE,
+        one_way: false,
     };
 
     pub const SENSOR: Self = Self {
         pos_solid_planes: BVec3::FALSE,
         neg_solid_planes: BVec3::FALSE,
         move_along: BVec3::FALSE,
+        one_way: false,
     };
 
-    /// Returns true if a velocity won't violate the constraints
-    ///
-    /// TODO: should probably return a `BVec3`, as it carries more info
+    /// A platform that is only solid when landed on from above, like [`Self::FLOOR`], but that
+    /// entities with [`Passthrough`] set drop through
+    pub const ONE_WAY: Self = Self {
+        pos_solid_planes: BVec3 {
+            x: false,
+            y: false,
+            z: true,
+        },
+        neg_solid_planes: BVec3::FALSE,
+        move_along: BVec3::FALSE,
+        one_way: true,
+    };
+
+    /// Look up one of the preset constraints by its lowercase name, ie. `wall`, `floor`,
+    /// `one_way`, `entity`, or `sensor`
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "wall" => Some(Self::WALL),
+            "floor" => Some(Self::FLOOR),
+            "one_way" => Some(Self::ONE_WAY),
+            "entity" => Some(Self::ENTITY),
+            "sensor" => Some(Self::SENSOR),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this isn't solid along any plane, so it can never block movement
+    #[must_use]
+    #[inline]
+    pub fn is_sensor(&self) -> bool {
+        !(self.pos_solid_planes.any() || self.neg_solid_planes.any())
+    }
+
+    /// Returns true if a velocity would violate the constraints along any axis. See
+    /// [`Self::violated_axes`]
     #[must_use]
     pub fn violates_solidity(&self, vel: Vec3) -> bool {
-        let signs = vel.as_ivec3().signum();
+        self.violated_axes(vel).any()
+    }
 
-        let check_neg = bvec_to_mask(self.pos_solid_planes).as_ivec3() * IVec3::NEG_ONE;
-        let check_pos = bvec_to_mask(self.neg_solid_planes).as_ivec3() * IVec3::ONE;
-        let signs_mask = signs.cmpeq(IVec3::ZERO);
+    /// Returns a `BVec3` where axes are true if moving along them with `vel` would pass through a
+    /// solid plane, ie. moving along +x into a collider solid on its negative x plane
+    #[must_use]
+    pub fn violated_axes(&self, vel: Vec3) -> BVec3 {
+        let signs = float_signs(vel);
 
-        !((signs_mask | (check_neg.cmpne(signs) & check_pos.cmpne(signs))).all())
+        (signs.cmpeq(IVec3::ONE) & self.neg_solid_planes)
+            | (signs.cmpeq(IVec3::NEG_ONE) & self.pos_solid_planes)
     }
 
     /// Returns a BVec where axes are true if applying impulse would violate constraints along that
     /// axis
     #[must_use]
     pub fn violates_movement(&self, impulse: Vec3) -> BVec3 {
-        impulse.as_ivec3().signum().cmpne(IVec3::ZERO) & self.move_along
+        float_signs(impulse).cmpne(IVec3::ZERO) & self.move_along
+    }
+}
+
+/// Combine two constraints, being solid along and able to be moved along any axis either one is.
+/// The combination is [`one_way`](Constraints::one_way) if either one is.
+///
+/// ```
+/// use bevy_math::BVec3;
+/// use pirate_sim_physics::collision::Constraints;
+///
+/// let wall_and_floor = Constraints::FLOOR | Constraints::WALL;
+/// assert_eq!(wall_and_floor.pos_solid_planes, BVec3::TRUE);
+/// ```
+impl std::ops::BitOr for Constraints {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self {
+            pos_solid_planes: self.pos_solid_planes | rhs.pos_solid_planes,
+            neg_solid_planes: self.neg_solid_planes | rhs.neg_solid_planes,
+            move_along: self.move_along | rhs.move_along,
+            one_way: self.one_way | rhs.one_way,
+        }
     }
 }
 
-#[derive(Reflect, Debug, Clone)]
+/// The sign of each axis of `vel`, where an axis of zero has a sign of zero.
+///
+/// This is taken from the float itself rather than after truncating to an integer, so that
+/// velocities of less than a tile per second still have a direction.
+#[inline]
+fn float_signs(vel: Vec3) -> IVec3 {
+    Vec3::select(vel.cmpeq(Vec3::ZERO), Vec3::ZERO, vel.signum()).as_ivec3()
+}
+
+/// A collider's collision during the last update, kept on its [`Collider`].
+///
+/// This is also sent as an event during [`PhysicsSet::Collision`] whenever a collider has its
+/// movement blocked, so that gameplay can react without polling every collider.
+#[derive(Event, Reflect, Debug, Clone)]
 pub struct EntityCollision {
+    /// The entity that collided
+    pub entity: Entity,
+    /// The tile it was on when it collided
+    pub tile: IVec3,
+    /// The axes its movement was blocked along
+    pub blocked: BVec3,
     pub other_entities: Vec<tile_cast::Hit<Entity>>,
+    /// [`Constraints::SENSOR`]-like colliders passed through or reached, which never block
+    /// movement and so aren't in `other_entities`
+    pub sensors: Vec<tile_cast::Hit<Entity>>,
     pub impulse: Vec3,
 }
 
@@ -123,6 +325,13 @@ pub struct EntityCollision {
 #[derive(Component, Debug, Reflect)]
 pub struct Collider {
     pub constraints: Constraints,
+    /// How many tiles this collider takes up along each axis, stretching out towards positive from
+    /// the tile its transform is on. A single tile by default.
+    ///
+    /// Something hitting any of these tiles hits this collider. When the collider moves itself
+    /// though, only the tile its transform is on is checked for collisions, so large colliders
+    /// are best kept for things that don't move on their own, like walls.
+    pub size: IVec3,
     collision: Option<EntityCollision>,
 }
 
@@ -133,167 +342,1298 @@ impl Collider {
         self.collision.as_ref()
     }
 
+    /// Forget this collider's last collision
+    #[inline]
+    pub fn clear_collision(&mut self) {
+        self.collision = None;
+    }
+
     #[must_use]
     #[inline]
     pub fn new(constraints: Constraints) -> Self {
         Self {
             constraints,
+            size: IVec3::ONE,
             collision: None,
         }
     }
+
+    /// This collider, taking up `size` tiles instead, see [`Collider::size`]
+    ///
+    /// ```
+    /// use bevy_math::IVec3;
+    /// use pirate_sim_physics::Collider;
+    ///
+    /// let crate_collider = Collider::entity().with_size(IVec3::new(2, 2, 1));
+    /// assert_eq!(crate_collider.tiles(IVec3::ZERO).count(), 4);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn with_size(mut self, size: IVec3) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Every tile this collider takes up while its transform is on `location`
+    pub fn tiles(&self, location: IVec3) -> impl Iterator<Item = IVec3> {
+        tiles_in(location, location + self.size - IVec3::ONE)
+    }
+
+    /// Shorthands for [`Collider::new`] with each of the preset [`Constraints`]
+    ///
+    /// ```
+    /// use pirate_sim_physics::{collision::Constraints, Collider};
+    ///
+    /// assert_eq!(Collider::wall().constraints, Constraints::WALL);
+    /// assert_eq!(Collider::floor().constraints, Constraints::FLOOR);
+    /// assert_eq!(Collider::entity().constraints, Constraints::ENTITY);
+    /// assert_eq!(Collider::sensor().constraints, Constraints::SENSOR);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn wall() -> Self {
+        Self::new(Constraints::WALL)
+    }
+
+    /// See [`Collider::wall`]
+    #[must_use]
+    #[inline]
+    pub fn floor() -> Self {
+        Self::new(Constraints::FLOOR)
+    }
+
+    /// See [`Collider::wall`]
+    #[must_use]
+    #[inline]
+    pub fn entity() -> Self {
+        Self::new(Constraints::ENTITY)
+    }
+
+    /// See [`Collider::wall`]
+    #[must_use]
+    #[inline]
+    pub fn sensor() -> Self {
+        Self::new(Constraints::SENSOR)
+    }
+}
+
+/// Which collision layers a [`Collider`] is on, and which layers it interacts with. Two colliders
+/// only interact when each one is on a layer in the other's mask, eg. a ghost whose mask leaves out
+/// the layer walls are on will pass right through them.
+///
+/// A collider without this is on, and interacts with, every layer.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct CollisionLayers {
+    /// Bitflags of the layers this collider is on
+    pub layers: u32,
+    /// Bitflags of the layers this collider interacts with
+    pub mask: u32,
+}
+
+impl CollisionLayers {
+    /// On and interacting with every layer, the same as having no layers at all
+    pub const ALL: Self = Self {
+        layers: u32::MAX,
+        mask: u32::MAX,
+    };
+
+    #[must_use]
+    #[inline]
+    pub fn new(layers: u32, mask: u32) -> Self {
+        Self { layers, mask }
+    }
+
+    /// Returns true if colliders with these layers interact with each other
+    #[must_use]
+    #[inline]
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        self.layers & other.mask != 0 && other.layers & self.mask != 0
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Returns true if the colliders of `a` and `b` interact, treating missing [`CollisionLayers`] as
+/// [`CollisionLayers::ALL`]
+#[inline]
+fn layers_interact(layers_q: &Query<&CollisionLayers>, a: Entity, b: Entity) -> bool {
+    let layers = |entity| layers_q.get(entity).copied().unwrap_or_default();
+
+    layers(a).interacts_with(&layers(b))
+}
+
+/// Set this to drop down through [`one_way`](Constraints::one_way) platforms, eg. while holding
+/// down. Anything else solid still blocks as usual.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut, Reflect)]
+pub struct Passthrough(pub bool);
+
+/// Returns true if `entity` drops through a collider with `constraints`, see [`Passthrough`]
+#[inline]
+fn passes_through(
+    passthrough_q: &Query<&Passthrough>,
+    entity: Entity,
+    constraints: &Constraints,
+) -> bool {
+    constraints.one_way && passthrough_q.get(entity).is_ok_and(|p| p.0)
+}
+
+/// The collider `entity` would be standing on at `location`, ie. one directly below it which is
+/// solid on its positive z plane, according to the [`CollisionMap`]
+pub(crate) fn floor_below(
+    collision_map: &CollisionMap,
+    entity: Entity,
+    location: IVec3,
+    layers_q: &Query<&CollisionLayers>,
+    passthrough_q: &Query<&Passthrough>,
+) -> Option<Entity> {
+    collision_map
+        .colliders_at(location - IVec3::Z)
+        .find(|(_, other, constraints)| {
+            *other != entity
+                && constraints.pos_solid_planes.z
+                && layers_interact(layers_q, entity, *other)
+                && !passes_through(passthrough_q, entity, constraints)
+        })
+        .map(|(_, other, _)| *other)
+}
+
+/// Keep `collision` on `collider`, adding to whatever it already collided with during an earlier
+/// pass of [`tile_cast_collision`] this update. `collision_order` is every collider with a collision so
+/// far, in the order they first collided.
+fn record_collision(
+    collider: &mut Collider,
+    collision_order: &mut Vec<Entity>,
+    collision: EntityCollision,
+) {
+    let Some(existing) = &mut collider.collision else {
+        collision_order.push(collision.entity);
+        collider.collision = Some(collision);
+        return;
+    };
+
+    existing.blocked |= collision.blocked;
+    existing.impulse += collision.impulse;
+
+    for hit in collision.other_entities {
+        let already_hit = existing
+            .other_entities
+            .iter()
+            .any(|h| h.data == hit.data && h.translation == hit.translation);
+
+        if !already_hit {
+            existing.other_entities.push(hit);
+        }
+    }
+
+    // only the latest pass knows how far we'll actually get
+    existing.sensors = collision.sensors;
+}
+
+/// How far along its path a collider gets this update, and what is in its way. See
+/// [`ColliderPath::resolve`]
+struct PathResolution<'a> {
+    /// The axes the collider is stopped along
+    blocked: BVec3,
+    /// Everything in the first step of the path that ran into anything
+    blockers: Vec<tile_cast::Hit<(&'a Entity, &'a Constraints)>>,
+    /// Colliders pushed out of the way, with where they are and the axis they're pushed along
+    pushing: Vec<(Entity, IVec3, usize)>,
+    /// How far along the path the collider gets this update
+    reached: IVec3,
+    /// How far along the path was checked for anything in the way
+    checked: IVec3,
+}
+
+/// Everything a [`ColliderPath`] needs to know about the colliders it could run into
+#[derive(SystemParam)]
+struct PathQueries<'w, 's> {
+    transforms: Query<'w, 's, &'static GlobalTransform>,
+    weights: Query<'w, 's, &'static Weight>,
+    layers: Query<'w, 's, &'static CollisionLayers>,
+    passthroughs: Query<'w, 's, &'static Passthrough>,
+}
+
+/// A collider's path from its tile to its predicted tile this update, for finding everything on the
+/// way that it could run into
+struct ColliderPath<'a> {
+    entity: Entity,
+    translation: IVec3,
+    predicted_location: IVec3,
+    vel: Vec3,
+    ticker: Vec3,
+    tile_stretch: TileStretch,
+    predicted_map: &'a CollisionMap,
+    sizes: &'a HashMap<Entity, IVec3>,
+    pushed_by: &'a HashMap<Entity, Entity>,
+    queries: &'a PathQueries<'a, 'a>,
+}
+
+impl<'a> ColliderPath<'a> {
+    /// Whether the collider could hit `other` at all this update
+    fn in_reach(&self, (opl, oe, oc): &(IVec3, Entity, Constraints)) -> bool {
+        let direction = self.vel.signum().as_ivec3();
+
+        // don't collide with ourselves, or whoever is pushing us
+        *oe != self.entity
+            && self.pushed_by.get(&self.entity) != Some(oe)
+            && layers_interact(&self.queries.layers, self.entity, *oe)
+            && !passes_through(&self.queries.passthroughs, self.entity, oc)
+            // this entity is actually close enough to be hit
+            && IVec3::cmple(*opl * direction, self.predicted_location * direction).all()
+    }
+
+    /// Someone else moving into the same tile as us this update only blocks us if they win it
+    fn blocks_us(&self, (opl, oe, _): &(IVec3, Entity, Constraints)) -> bool {
+        let contested = *opl == self.predicted_location
+            && self.predicted_location != self.translation
+            && self.queries.transforms.get(*oe).is_ok_and(|t| {
+                let size = self.sizes.get(oe).copied().unwrap_or(IVec3::ONE);
+                let location = t.location(self.tile_stretch);
+
+                !tiles_in(location, location + size - IVec3::ONE).any(|l| l == *opl)
+            });
+
+        !contested
+            || !wins_contested_tile(
+                (self.entity, self.queries.weights.get(self.entity).ok()),
+                (*oe, self.queries.weights.get(*oe).ok()),
+            )
+    }
+
+    /// Tile cast along the path, returning the sensors it passes through and every collider it
+    /// could be blocked by, including those sharing its tile
+    fn cast(
+        &self,
+        tracing: bool,
+    ) -> (
+        Vec<tile_cast::Hit<Entity>>,
+        Vec<tile_cast::Hit<(&'a Entity, &'a Constraints)>>,
+    ) {
+        let origin = || tile_cast::Origin {
+            tile: self.translation,
+            ticker: self.ticker,
+        };
+
+        // the ray never leaves the box between where we are and where we're predicted to be
+        let nearby: Vec<_> = self
+            .predicted_map
+            .colliders_in(
+                self.translation.min(self.predicted_location),
+                self.translation.max(self.predicted_location),
+            )
+            .filter(|c| self.in_reach(c))
+            .collect();
+
+        // sensors are cast separately from everything else, so that they can never change whether
+        // or not we're blocked, only be recorded
+        let sensor_hits = tile_cast_traced(
+            origin(),
+            self.vel,
+            self.tile_stretch,
+            false,
+            tracing,
+            nearby
+                .iter()
+                .copied()
+                .filter(|(_, _, oc)| oc.is_sensor())
+                .map(|(l, e, _)| (*e, l)),
+        )
+        .collect();
+
+        let hit_entities = tile_cast_traced(
+            origin(),
+            self.vel,
+            self.tile_stretch,
+            // entities sharing our tile are needed for things like taking velocity from the ground
+            true,
+            tracing,
+            nearby
+                .iter()
+                .copied()
+                .filter(|c| self.blocks_us(c))
+                .filter(|(_, _, oc)| oc.violates_solidity(self.vel))
+                // put it so that constraint & entity id are in data field
+                .map(|(l, e, c)| ((e, c), l)),
+        )
+        .collect();
+
+        (sensor_hits, hit_entities)
+    }
+
+    /// Everything on the tile `offset` from `from` that is solid against moving along `offset`
+    fn blockers_at(
+        &self,
+        from: IVec3,
+        offset: IVec3,
+        normal: IVec3,
+    ) -> Vec<tile_cast::Hit<(&'a Entity, &'a Constraints)>> {
+        let along_path = from - self.translation + offset;
+
+        self.predicted_map
+            .colliders_at(from + offset)
+            .filter(|c| self.in_reach(c))
+            .filter(|c| self.blocks_us(c))
+            .filter(|(_, _, c)| c.violates_solidity(offset.as_vec3()))
+            .map(|(l, e, c)| tile_cast::Hit {
+                translation: *l,
+                offset: along_path,
+                distance: along_path.as_vec3().length(),
+                normal,
+                data: (e, c),
+            })
+            .collect()
+    }
+
+    /// The collider on one side of a step, and where it is, if it can be pushed along `offset`. See
+    /// the module docs for pushing
+    fn pushable(
+        &self,
+        side: &[tile_cast::Hit<(&Entity, &Constraints)>],
+        offset: IVec3,
+        movable: &impl Fn(Entity) -> bool,
+    ) -> Option<(Entity, IVec3)> {
+        let [hit] = side else {
+            return None;
+        };
+        let (&pushed, pushed_constraints) = hit.data;
+
+        let can_move = (pushed_constraints.move_along & offset.cmpne(IVec3::ZERO)).any()
+            && movable(pushed)
+            && self
+                .queries
+                .transforms
+                .get(pushed)
+                .is_ok_and(|t| t.location(self.tile_stretch) == hit.translation);
+        let behind_is_free = !self
+            .predicted_map
+            .colliders_at(hit.translation + offset)
+            .any(|(_, e, c)| {
+                *e != self.entity
+                    && *e != pushed
+                    && c.violates_solidity(offset.as_vec3())
+                    && layers_interact(&self.queries.layers, pushed, *e)
+            });
+
+        (can_move && behind_is_free).then_some((pushed, hit.translation))
+    }
+
+    /// Resolve a single step of the path, of at most one tile along each axis, from `from`. Returns
+    /// the axes it's blocked along, what it ran into, and what it pushes
+    fn resolve_step(
+        &self,
+        from: IVec3,
+        substep: IVec3,
+        movable: &impl Fn(Entity) -> bool,
+    ) -> (
+        BVec3,
+        Vec<tile_cast::Hit<(&'a Entity, &'a Constraints)>>,
+        Vec<(Entity, IVec3, usize)>,
+    ) {
+        let mut blocked = BVec3::FALSE;
+        let mut blockers = Vec::new();
+        let mut pushing = Vec::new();
+
+        for axis in 0..3 {
+            if substep[axis] == 0 {
+                continue;
+            }
+
+            let mut offset = IVec3::ZERO;
+            offset[axis] = substep[axis];
+
+            let side = self.blockers_at(from, offset, -offset);
+            if side.is_empty() {
+                continue;
+            }
+
+            match self.pushable(&side, offset, movable) {
+                Some((pushed, pushed_location)) => {
+                    pushing.push((pushed, pushed_location, axis));
+                }
+                None => blocked.set(axis, true),
+            }
+            blockers.extend(side);
+        }
+
+        let remaining = IVec3::select(blocked, IVec3::ZERO, substep);
+        if remaining.cmpne(IVec3::ZERO).bitmask().count_ones() > 1 {
+            // a step from the centre of one tile to the centre of a diagonal one passes exactly
+            // through the corner, which tile casting counts as entering through z, then y, then x
+            let normal = if remaining.z != 0 {
+                IVec3::new(0, 0, -remaining.z)
+            } else {
+                IVec3::new(0, -remaining.y, 0)
+            };
+
+            for hit in self.blockers_at(from, remaining, normal) {
+                blocked |= hit.data.1.violated_axes(remaining.as_vec3());
+                blockers.push(hit);
+            }
+        }
+
+        (blocked, blockers, pushing)
+    }
+
+    /// Walk the path a step at a time until something is in the way. `movable` is whether a
+    /// collider has everything it needs to be pushed.
+    ///
+    /// An entity can move any number of tiles along each axis per frame (see movement's
+    /// `finalize_movement`), so its path is walked a step at a time, each step being at most a single
+    /// tile along each axis (see [`path_through`]). Anything that could block a step is either in the
+    /// tile it starts from or a single step away, and each axis of a step is resolved on its own:
+    ///
+    /// - an axis is blocked if the tile one step along just that axis is solid against moving along
+    ///   it. This lets an entity slide along a wall, losing only its velocity into the wall, and
+    ///   stops it from cutting diagonally past corners
+    /// - if what's left of the step is still diagonal, it is blocked along every axis that the tile
+    ///   it would end up in is solid against, eg. running head on into a wall's corner
+    ///
+    /// Colliders already sharing the collider's tile are left to the caller.
+    fn resolve(&self, movable: impl Fn(Entity) -> bool) -> PathResolution<'a> {
+        let step = self.predicted_location - self.translation;
+
+        let mut resolution = PathResolution {
+            blocked: BVec3::FALSE,
+            blockers: Vec::new(),
+            pushing: Vec::new(),
+            reached: step,
+            checked: step,
+        };
+
+        let mut last = IVec3::ZERO;
+        for next in path_through(step) {
+            let (blocked, blockers, pushing) =
+                self.resolve_step(self.translation + last, next - last, &movable);
+
+            if blockers.is_empty() {
+                last = next;
+                continue;
+            }
+
+            // the first step that runs into anything is where we collide. Along the axes it's
+            // blocked on we stop just short of it, next to whatever is in the way
+            if blocked.any() {
+                resolution.reached = IVec3::select(blocked, last, next);
+                resolution.checked = next;
+            }
+
+            resolution.blocked = blocked;
+            resolution.blockers = blockers;
+            resolution.pushing = pushing;
+            break;
+        }
+
+        resolution
+    }
+}
+
+/// Have everything `pusher` pushes move along with it, by giving each its velocities and ticker
+/// along the axis it is pushed along
+fn push_colliders(
+    (pusher, name): (Entity, &str),
+    pushing: Vec<(Entity, IVec3, usize)>,
+    (total, relative, ticker): (Vec3, Vec3, Vec3),
+    (total_vel_q, relative_vel_q, ticker_q): (
+        &mut Query<&mut TotalVelocity>,
+        &mut Query<&mut RelativeVelocity>,
+        &mut Query<&mut Ticker>,
+    ),
+    pushed_by: &mut HashMap<Entity, Entity>,
+    changed: &mut Vec<(Entity, IVec3)>,
+    tracing: bool,
+) {
+    for (pushed, pushed_location, axis) in pushing {
+        pushed_by.insert(pushed, pusher);
+        changed.push((pushed, pushed_location));
+
+        if tracing {
+            trace!("{name} is pushing {pushed:?} along axis {axis}");
+        }
+
+        if let Ok(mut pushed_total) = total_vel_q.get_mut(pushed) {
+            pushed_total.0[axis] = total[axis];
+        }
+        if let Ok(mut pushed_relative) = relative_vel_q.get_mut(pushed) {
+            pushed_relative.0[axis] = relative[axis];
+        }
+        if let Ok(mut pushed_ticker) = ticker_q.get_mut(pushed) {
+            pushed_ticker.0[axis] = ticker[axis];
+        }
+    }
 }
 
 #[allow(clippy::too_many_lines)]
 /// Use tile casting to implement smooth collision impulses
+///
+/// Resolving one collider can stop it from leaving a tile that another was about to move into, eg.
+/// the front of a row of movers being stopped by a wall. So after each pass over every collider,
+/// anything whose predicted tile changed is moved in the [`CollisionMap`], and whatever is close
+/// enough to be affected by that is resolved again. This repeats until nothing changes, or
+/// [`MaxCollisionIterations`](crate::MaxCollisionIterations) passes have been made.
 fn tile_cast_collision(
     mut total_vel_q: Query<&mut TotalVelocity>,
     mut relative_vel_q: Query<&mut RelativeVelocity>,
     mut collider_q: Query<&mut Collider>,
-    transform_q: Query<&GlobalTransform>,
-    ticker_q: Query<&Ticker>,
+    mut ticker_q: Query<&mut Ticker>,
+    frozen_q: Query<(), With<PhysicsFrozen>>,
     name_q: Query<&Name>,
+    path_queries: PathQueries,
+    mut restitution_q: Query<&mut Restitution>,
+    tile_stretch: Res<TileStretch>,
+    mut predicted_map: ResMut<CollisionMap>,
+    max_iterations: Res<crate::MaxCollisionIterations>,
+    log_config: Res<crate::PhysicsLogConfig>,
+    mut collision_events: EventWriter<EntityCollision>,
+) {
+    let tracing = log_config.trace_collision;
+
+    // pushed colliders are about to move out of their pusher's way, so it can't block them
+    let mut pushed_by: HashMap<Entity, Entity> = HashMap::new();
+
+    let sizes: HashMap<Entity, IVec3> = predicted_map
+        .iter()
+        .filter_map(|(_, e, _)| Some((*e, collider_q.get(*e).ok()?.size)))
+        .collect();
+    let delta_time = predicted_map.delta_seconds;
+
+    // the tiles colliders moved out of or into during the last pass
+    let mut changed_tiles: HashSet<IVec3> = HashSet::new();
+    // colliders whose predicted tile was changed by resolving collisions
+    let mut repredicted = HashSet::new();
+    // every collider that collided with something this update, in the order they first did
+    let mut collision_order = Vec::new();
+
+    for pass in 0..max_iterations.0.max(1) {
+        let mut resolved = HashSet::new();
+        // colliders whose velocity or ticker was changed, along with where they were predicted to be
+        let mut changed = Vec::new();
+
+        // see build_collision_map
+        for &(predicted_location, entity, constraints) in &**predicted_map {
+            // the rest of a large collider's tiles come after the one its transform is on, and are
+            // only there to be hit
+            if !resolved.insert(entity) {
+                continue;
+            }
+
+            // SAFETY: entity was originally taken from a query over <(Entity, &Collider)> in the
+            // current frame
+            let mut collider = unsafe { collider_q.get_mut(entity).unwrap_unchecked() };
+
+            // clear collider.collisions. This isn't really the right place to do this but it's fine
+            if pass == 0 {
+                collider.collision = None;
+            }
+
+            let Some((vel, relative)) = Option::zip(
+                total_vel_q.get(entity).ok(),
+                relative_vel_q.get(entity).ok(),
+            ) else {
+                continue;
+            };
+
+            let name = name_q
+                .get(entity)
+                .map_or("Unnamed".to_owned(), ToString::to_string);
+            let translation = path_queries
+                .transforms
+                .get(entity)
+                .expect("Entity with collider but no transform")
+                .location(*tile_stretch);
+
+            // This should never happen. Leave it in as common-sense assert
+            debug_assert!(
+                (translation.as_vec3() * vel.signum())
+                    .cmple(predicted_location.as_vec3() * vel.signum())
+                    .all(),
+                "Predicted to move opposite of velocity"
+            );
+
+            if vel.0 == Vec3::ZERO {
+                continue;
+            }
+
+            // only colliders that could hit, push or contest one of the changed tiles can be
+            // resolved any differently than last pass
+            let reach_min = translation.min(predicted_location) - IVec3::ONE;
+            let reach_max = translation.max(predicted_location) + IVec3::ONE;
+            if pass > 0
+                && !changed_tiles
+                    .iter()
+                    .any(|tile| tile.cmpge(reach_min).all() && tile.cmple(reach_max).all())
+            {
+                continue;
+            }
+
+            let ticker = utils::get_or_zero(&ticker_q, entity);
+            if tracing {
+                trace!("checking collision of {name} at predicted_location {predicted_location}, real location {translation}:{ticker}");
+            }
+
+            let path = ColliderPath {
+                entity,
+                translation,
+                predicted_location,
+                vel: vel.0,
+                ticker,
+                tile_stretch: *tile_stretch,
+                predicted_map: &predicted_map,
+                sizes: &sizes,
+                pushed_by: &pushed_by,
+                queries: &path_queries,
+            };
+
+            let (mut sensor_hits, mut hit_entities) = path.cast(tracing);
+
+            let step = predicted_location - translation;
+            let PathResolution {
+                mut blocked,
+                blockers,
+                pushing,
+                mut reached,
+                checked,
+            } = path.resolve(|pushed| {
+                total_vel_q.contains(pushed)
+                    && relative_vel_q.contains(pushed)
+                    && ticker_q.contains(pushed)
+                    && !frozen_q.contains(pushed)
+            });
+
+            // a collider already sharing our tile blocks every axis it is solid against. Something
+            // only left on our tile because it was blocked this update, eg. by losing the tile we're
+            // moving into, doesn't stop us from leaving
+            for hit in hit_entities
+                .iter()
+                .filter(|hit| hit.offset == IVec3::ZERO && !repredicted.contains(hit.data.0))
+            {
+                let in_tile = hit.data.1.violated_axes(**vel);
+                blocked |= in_tile;
+                reached = IVec3::select(in_tile, IVec3::ZERO, reached);
+            }
+
+            // nothing past the part of our path that was checked is reached this frame
+            let within_checked = |offset: IVec3| {
+                offset.cmpge(checked.min(IVec3::ZERO)).all()
+                    && offset.cmple(checked.max(IVec3::ZERO)).all()
+            };
+            hit_entities.retain(|hit| within_checked(hit.offset));
+            sensor_hits.retain(|hit| within_checked(hit.offset));
+
+            // We only get as far as `reached` this frame. The ticker is cleared back to it along the
+            // axes we were stopped on, so that only what's left of our velocity moves us from there,
+            // eg. a bounce. Along the rest it's wound back so that it makes exactly `reached`
+            let stopped = blocked & constraints.move_along;
+            let cut_short = reached.cmpne(step) & constraints.move_along;
+            if let Ok(mut entity_ticker) = ticker_q.get_mut(entity) {
+                let wound_back = reached.as_vec3() - relative.0 * delta_time;
+                let new_ticker = Vec3::select(
+                    stopped,
+                    reached.as_vec3(),
+                    Vec3::select(cut_short, wound_back, entity_ticker.0),
+                );
+
+                if new_ticker != entity_ticker.0 {
+                    entity_ticker.0 = new_ticker;
+                    changed.push((entity, predicted_location));
+                }
+            }
+
+            if tracing && blocked.any() {
+                trace!("{name} is blocked along {blocked}");
+            }
+
+            // anything that blocked us was hit, even if the cast ray didn't pass through its tile
+            for blocker in blockers {
+                let already_hit = hit_entities.iter().any(|hit| {
+                    hit.data.0 == blocker.data.0 && hit.translation == blocker.translation
+                });
+
+                if !already_hit {
+                    hit_entities.push(blocker);
+                }
+            }
+
+            // the closest hit decides how far away the collision is
+            let Some(closest_distance) = hit_entities
+                .iter()
+                .min_by(|a, b| a.cmp_distance(b))
+                .map(|hit| hit.distance)
+            else {
+                if tracing {
+                    trace!("No possible hit");
+                }
+
+                if !sensor_hits.is_empty() || collider.collision.is_some() {
+                    record_collision(
+                        &mut collider,
+                        &mut collision_order,
+                        EntityCollision {
+                            entity,
+                            tile: translation,
+                            blocked: BVec3::FALSE,
+                            other_entities: Vec::new(),
+                            sensors: sensor_hits,
+                            impulse: Vec3::ZERO,
+                        },
+                    );
+                }
+
+                continue;
+            };
+
+            // a sensor past whatever we hit won't be reached
+            sensor_hits.retain(|hit| hit.distance <= closest_distance);
+
+            // Whatever blocks us is always in the very next step, so the whole of our velocity along
+            // a blocked axis is cancelled rather than slowed down. Only axes we can be moved along
+            // are changed. The impulse is subtracted from both total and relative velocity, so it has
+            // the same sign as the velocity it cancels. With restitution it goes past cancelling, and
+            // reflects some of that velocity back the way it came
+            let coefficient = restitution_q
+                .get(entity)
+                .map_or(0., |r| r.coefficient.max(0.));
+            let bounce = 1. + coefficient;
+            let impulse =
+                bvec_to_mask(blocked) * bvec_to_mask(constraints.move_along) * vel.0 * bounce;
+
+            if tracing {
+                trace!("subtracting impulse {impulse}");
+            }
+            debug_assert!(impulse.is_finite());
+
+            let collision = EntityCollision {
+                entity,
+                tile: translation,
+                blocked,
+                other_entities: hit_entities.iter().map(|h| h.map(|(e, _)| *e)).collect(),
+                sensors: sensor_hits,
+                impulse,
+            };
+            record_collision(&mut collider, &mut collision_order, collision);
+
+            // SAFETY: we should have already returned if these queries are invalid
+            let mut vel = unsafe { total_vel_q.get_mut(entity).unwrap_unchecked() };
+            let mut r_vel = unsafe { relative_vel_q.get_mut(entity).unwrap_unchecked() };
+
+            vel.0 -= impulse;
+            r_vel.0 -= impulse;
+
+            // the rest of our velocity is worked out again next frame, so the bounce is kept as a
+            // rebound for it to carry us anywhere
+            if coefficient > 0. {
+                if let Ok(mut restitution) = restitution_q.get_mut(entity) {
+                    restitution.rebound -= impulse;
+                }
+            }
+
+            if impulse != Vec3::ZERO {
+                changed.push((entity, predicted_location));
+            }
+
+            if tracing {
+                trace!("new vel r: {} t: {}", r_vel.0, vel.0);
+            }
+
+            let velocities = (vel.0, r_vel.0, ticker);
+            push_colliders(
+                (entity, &name),
+                pushing,
+                velocities,
+                (&mut total_vel_q, &mut relative_vel_q, &mut ticker_q),
+                &mut pushed_by,
+                &mut changed,
+                tracing,
+            );
+        }
+
+        changed_tiles.clear();
+        let mut moved = HashSet::new();
+        for (entity, old_location) in changed {
+            if !moved.insert(entity) {
+                continue;
+            }
+
+            let location = calc_movement(
+                total_vel_q.get(entity).ok(),
+                ticker_q.get(entity).ok(),
+                delta_time,
+            ) + path_queries
+                .transforms
+                .get(entity)
+                .expect("Entity with collider but no transform")
+                .location(*tile_stretch);
+
+            if location != old_location {
+                let moved_collider = collider_q
+                    .get(entity)
+                    .expect("Entity in the map has a collider");
+                changed_tiles.extend(moved_collider.tiles(old_location));
+                changed_tiles.extend(moved_collider.tiles(location));
+
+                predicted_map.move_predicted(entity, old_location, location);
+                repredicted.insert(entity);
+            }
+        }
+
+        if changed_tiles.is_empty() {
+            break;
+        }
+
+        if tracing {
+            trace!("collision pass {pass} moved colliders on {changed_tiles:?}");
+        }
+    }
+
+    for entity in collision_order {
+        if let Some(collision) = collider_q.get(entity).ok().and_then(Collider::collision) {
+            if collision.blocked.any() {
+                collision_events.send(collision.clone());
+            }
+        }
+    }
+}
+
+/// How close an entity must be to resting on a floor before it is snapped onto it.
+///
+/// An entity standing on a floor will still have gravity pulling it down, which slowly fills its
+/// [`Ticker`] until collision cancels its velocity, at which point it starts over. Snapping stops
+/// this cycle by clearing the downwards part of its ticker and velocity whenever the ticker is
+/// within `tolerance` of a tile of resting on the floor.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct FloorSnap {
+    pub tolerance: f32,
+}
+
+impl Default for FloorSnap {
+    fn default() -> Self {
+        Self { tolerance: 0.5 }
+    }
+}
+
+/// Snap entities resting on a floor onto it. See [`FloorSnap`]
+fn snap_to_floor(
+    mut mover_q: Query<(
+        Entity,
+        &mut Ticker,
+        &mut TotalVelocity,
+        &mut RelativeVelocity,
+        &mut Transform,
+        &GlobalTransform,
+    )>,
     tile_stretch: Res<TileStretch>,
-    predicted_map: Res<CollisionMap>,
+    collision_map: Res<CollisionMap>,
+    layers_q: Query<&CollisionLayers>,
+    passthrough_q: Query<&Passthrough>,
+    floor_snap: Res<FloorSnap>,
 ) {
-    // see build_collision_map
-    for &(predicted_location, entity, constraints) in &**predicted_map {
-        // SAFETY: entity was originally taken from a query over <(Entity, &Collider)> in the
-        // current frame
-        let mut collider = unsafe { collider_q.get_mut(entity).unwrap_unchecked() };
-
-        // clear collider.collisions. This isn't really the right place to do this but it's fine
-        collider.collision = None;
-
-        let Some((vel, _)) = Option::zip(
-            total_vel_q.get(entity).ok(),
-            relative_vel_q.get(entity).ok(),
-        ) else {
+    for (entity, mut ticker, mut total, mut relative, mut transform, global_transform) in
+        &mut mover_q
+    {
+        if total.z > 0. || ticker.z > 0. || ticker.z <= -floor_snap.tolerance {
             continue;
-        };
+        }
 
-        let name = name_q
-            .get(entity)
-            .map_or("Unnamed".to_owned(), ToString::to_string);
-        let translation = transform_q
+        let on_floor = floor_below(
+            &collision_map,
+            entity,
+            global_transform.location(*tile_stretch),
+            &layers_q,
+            &passthrough_q,
+        )
+        .is_some();
+
+        if on_floor {
+            ticker.0.z = 0.;
+            total.0.z = 0.;
+            relative.0.z = 0.;
+            transform.translation.z = transform.translation.z.round();
+        }
+    }
+}
+
+/// The entity this one is standing on, if any. That is, a collider directly below it (at a z
+/// offset of -1) which is solid on its positive z plane.
+///
+/// Add this to an entity to have it kept up to date during [`PhysicsSet::Collision`]
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, Reflect)]
+pub struct StandingOn(pub Option<Entity>);
+
+fn update_standing_on(
+    mut standing_q: Query<(Entity, &mut StandingOn, &GlobalTransform)>,
+    tile_stretch: Res<TileStretch>,
+    collision_map: Res<CollisionMap>,
+    layers_q: Query<&CollisionLayers>,
+    passthrough_q: Query<&Passthrough>,
+) {
+    for (entity, mut standing_on, global_transform) in &mut standing_q {
+        let support = floor_below(
+            &collision_map,
+            entity,
+            global_transform.location(*tile_stretch),
+            &layers_q,
+            &passthrough_q,
+        );
+
+        // avoid triggering change detection every frame
+        if standing_on.0 != support {
+            standing_on.0 = support;
+        }
+    }
+}
+
+/// Every other collider sharing this entity's tile right now, as opposed to the predicted
+/// collisions in [`Collider::collision`]. eg. for standing in fire
+///
+/// Add this to an entity with a [`Collider`] to have it kept up to date after transforms propagate
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq, Deref, Reflect)]
+pub struct CurrentOverlaps(pub Vec<Entity>);
+
+fn update_current_overlaps(
+    mut overlaps_q: Query<(Entity, &mut CurrentOverlaps)>,
+    collider_q: Query<(Entity, &Collider, &GlobalTransform)>,
+    tile_stretch: Res<TileStretch>,
+) {
+    let by_tile = colliders_by_tile(&collider_q, *tile_stretch);
+
+    for (entity, mut overlaps) in &mut overlaps_q {
+        let overlapping = collider_q
             .get(entity)
-            .expect("Entity with collider but no transform")
-            .location(*tile_stretch);
-
-        // This should never happen. Leave it in as common-sense assert
-        debug_assert!(
-            (translation.as_vec3() * vel.signum())
-                .cmple(predicted_location.as_vec3() * vel.signum())
-                .all(),
-            "Predicted to move opposite of velocity"
+            .map(|(_, collider, transform)| {
+                sharing_tiles(&by_tile, collider.tiles(transform.location(*tile_stretch)))
+                    .filter(|other| *other != entity)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // avoid triggering change detection every frame
+        if overlaps.0 != overlapping {
+            overlaps.0 = overlapping;
+        }
+    }
+}
+
+/// Every collider, grouped by each of the tiles it takes up
+fn colliders_by_tile(
+    collider_q: &Query<(Entity, &Collider, &GlobalTransform)>,
+    tile_stretch: TileStretch,
+) -> HashMap<IVec3, Vec<Entity>> {
+    let mut by_tile: HashMap<IVec3, Vec<Entity>> = HashMap::new();
+
+    for (entity, collider, transform) in collider_q {
+        for location in collider.tiles(transform.location(tile_stretch)) {
+            by_tile.entry(location).or_default().push(entity);
+        }
+    }
+
+    by_tile
+}
+
+/// Every collider on any of `tiles`, once each
+fn sharing_tiles<'a>(
+    by_tile: &'a HashMap<IVec3, Vec<Entity>>,
+    tiles: impl Iterator<Item = IVec3> + 'a,
+) -> impl Iterator<Item = Entity> + 'a {
+    let mut seen = HashSet::new();
+
+    tiles
+        .filter_map(|location| by_tile.get(&location))
+        .flatten()
+        .copied()
+        .filter(move |entity| seen.insert(*entity))
+}
+
+/// Sent when `other` starts sharing a tile with `sensor`, a collider that
+/// [`is_sensor`](Constraints::is_sensor). eg. for pressure plates and goal zones
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEnter {
+    pub sensor: Entity,
+    pub other: Entity,
+}
+
+/// Sent when `other` stops sharing a tile with `sensor`, after a [`TriggerEnter`], including
+/// because either of them was despawned
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerExit {
+    pub sensor: Entity,
+    pub other: Entity,
+}
+
+/// What each sensor overlapped last update, to tell when something enters or exits it
+#[derive(Resource, Debug, Default)]
+struct SensorOverlaps(HashMap<Entity, Vec<Entity>>);
+
+fn send_trigger_events(
+    collider_q: Query<(Entity, &Collider, &GlobalTransform)>,
+    layers_q: Query<&CollisionLayers>,
+    tile_stretch: Res<TileStretch>,
+    mut last_overlaps: ResMut<SensorOverlaps>,
+    mut enter_events: EventWriter<TriggerEnter>,
+    mut exit_events: EventWriter<TriggerExit>,
+) {
+    let by_tile = colliders_by_tile(&collider_q, *tile_stretch);
+
+    let overlaps: HashMap<_, _> = collider_q
+        .iter()
+        .filter(|(_, collider, _)| collider.constraints.is_sensor())
+        .map(|(sensor, collider, transform)| {
+            let overlapping: Vec<_> =
+                sharing_tiles(&by_tile, collider.tiles(transform.location(*tile_stretch)))
+                    .filter(|other| *other != sensor && layers_interact(&layers_q, sensor, *other))
+                    .collect();
+
+            (sensor, overlapping)
+        })
+        .collect();
+
+    for (sensor, last) in &last_overlaps.0 {
+        let current = overlaps.get(sensor);
+
+        exit_events.send_batch(
+            last.iter()
+                .filter(|other| !current.is_some_and(|c| c.contains(other)))
+                .map(|other| TriggerExit {
+                    sensor: *sensor,
+                    other: *other,
+                }),
+        );
+    }
+
+    for (sensor, current) in &overlaps {
+        let last = last_overlaps.0.get(sensor);
+
+        enter_events.send_batch(
+            current
+                .iter()
+                .filter(|other| !last.is_some_and(|l| l.contains(other)))
+                .map(|other| TriggerEnter {
+                    sensor: *sensor,
+                    other: *other,
+                }),
         );
+    }
+
+    last_overlaps.0 = overlaps;
+}
+
+/// Reverse this entity's [`MovementGoal`](crate::MovementGoal) along any axis it is blocked on, so
+/// that it bounces back and forth between walls. eg. for simple patrolling enemies
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct BounceOnCollision;
 
-        if vel.0 == Vec3::ZERO {
+fn bounce_on_collision(
+    mut bouncer_q: Query<(&Collider, &mut crate::MovementGoal), With<BounceOnCollision>>,
+) {
+    for (collider, mut goal) in &mut bouncer_q {
+        let Some(collision) = collider.collision() else {
             continue;
+        };
+
+        // the impulse is taken away from velocity, so it shares a sign with the blocked movement
+        let blocked = collision.impulse.cmpne(Vec3::ZERO)
+            & float_signs(collision.impulse).cmpeq(float_signs(goal.0));
+
+        if blocked.any() {
+            goal.0 = Vec3::select(blocked, -goal.0, goal.0);
         }
+    }
+}
 
-        let ticker = utils::get_or_zero(&ticker_q, entity);
-        trace!("checking collision of {name} at predicted_location {predicted_location}, real location {translation}:{ticker}");
-
-        // once this is correct, instead of folding to closest entity and checking that, go through
-        // every possibly hit entity and bitor its constraints together
-        let possibly_hit_entities = predicted_map.iter().filter(|(opl, oe, oc)| {
-            // don't collide with ourselves
-            *oe != entity
-            // this entity is actually close enough to be hit; 
-                && IVec3::cmple(
-                    *opl * vel.0.signum().as_ivec3(),
-                    predicted_location * vel.0.signum().as_ivec3()
-                )
-                .all()
-            //  add check against vel.0.signum(), 
-            && oc.violates_solidity(**vel)
-        });
+/// How much of its velocity this collider keeps when it is blocked, reflected back the way it came.
+///
+/// 0 stops it dead, the same as having no restitution, and 1 is a perfect bounce. Only the axes it
+/// was blocked along are reflected.
+///
+/// The rest of a collider's velocity, eg. gravity, is worked out again every frame, so the bounce is
+/// kept as a rebound that is added to its [`RelativeVelocity`] until it runs out. The rebound along z
+/// slows down by the [`Gravity`](crate::Gravity) pulling on the collider every second, so something
+/// bouncing off the floor rises, falls back down, and bounces a little lower each time. Rebounds
+/// along x and y, and any rebound on a collider without gravity, don't slow down.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct Restitution {
+    pub coefficient: f32,
+    rebound: Vec3,
+}
 
-        let hit_entities: Vec<_> = tile_cast(
-            tile_cast::Origin {
-                tile: translation,
-                ticker,
-            },
-            **vel,
-            *tile_stretch,
-            possibly_hit_entities.map(|(l, a, b)| ((a, b), l)), // put it so that constraint & entity id are in data field
-        )
-        .collect();
+impl Restitution {
+    #[must_use]
+    pub fn new(coefficient: f32) -> Self {
+        Self {
+            coefficient,
+            rebound: Vec3::ZERO,
+        }
+    }
 
-        // This fold should work because there's only one shortest distance so once we get the
-        // vector of entities with that shortest distance it'll never get replaced
-        //
-        // This fold finds only the entities we will collide with, assuming we do collide. This is
-        // used for future checks of things like
-        let Some(closest_distance) = hit_entities.iter().fold(None, |acc, elem| {
-            Some(match acc {
-                Some(acc) if acc > elem.distance => acc,
-                _ => elem.distance,
+    /// The velocity this collider is still bouncing away from whatever it last hit with
+    #[must_use]
+    pub fn rebound(&self) -> Vec3 {
+        self.rebound
+    }
+
+    /// Slow the rebound along z down by up to `max_change`
+    pub(crate) fn slow_rebound(&mut self, max_change: f32) {
+        let speed = (self.rebound.z.abs() - max_change).max(0.);
+        self.rebound.z = self.rebound.z.signum() * speed;
+    }
+}
+
+/// How many tiles away [`escape_solids`] will look for a free tile
+const ESCAPE_RADIUS: i32 = 3;
+
+/// Whether a collider fills its entire tile, ie. nothing should ever be inside of it
+#[inline]
+fn fills_tile(constraints: &Constraints) -> bool {
+    constraints.pos_solid_planes.all() && constraints.neg_solid_planes.all()
+}
+
+/// Push moving entities that ended up inside of a solid tile, eg. from a teleport or spawn, towards
+/// the nearest free tile on the same z level.
+///
+/// A stuck entity has all of its velocity cancelled by collision, so it would otherwise never get
+/// out. Instead, it is moved a single tile per frame towards the closest free tile, preferring
+/// orthogonal neighbours.
+fn escape_solids(
+    mut mover_q: Query<(
+        Entity,
+        &mut Ticker,
+        &mut TotalVelocity,
+        &mut RelativeVelocity,
+        &mut Transform,
+        &GlobalTransform,
+        Option<&Name>,
+    )>,
+    tile_stretch: Res<TileStretch>,
+    collision_map: Res<CollisionMap>,
+    layers_q: Query<&CollisionLayers>,
+) {
+    let is_solid = |entity: Entity, tile: IVec3| {
+        collision_map
+            .colliders_at(tile)
+            .any(|(_, other, constraints)| {
+                *other != entity
+                    && fills_tile(constraints)
+                    && layers_interact(&layers_q, entity, *other)
             })
-        }) else {
-            trace!("No possible hit");
+    };
+
+    for (entity, mut ticker, mut total, mut relative, mut transform, global_transform, name) in
+        &mut mover_q
+    {
+        let location = global_transform.location(*tile_stretch);
+
+        if !is_solid(entity, location) {
+            continue;
+        }
+
+        let mut candidates: Vec<_> = (-ESCAPE_RADIUS..=ESCAPE_RADIUS)
+            .flat_map(|x| (-ESCAPE_RADIUS..=ESCAPE_RADIUS).map(move |y| IVec3::new(x, y, 0)))
+            .filter(|offset| *offset != IVec3::ZERO)
+            .collect();
+        // closest first, and orthogonal before diagonal at the same distance
+        candidates
+            .sort_by_key(|offset| (offset.abs().max_element(), offset.abs().x + offset.abs().y));
+
+        let Some(free) = candidates
+            .into_iter()
+            .find(|offset| !is_solid(entity, location + *offset))
+        else {
+            warn!(
+                "{} is stuck inside a solid tile at {location} with nowhere to go",
+                name.map_or("Unnamed".to_string(), ToString::to_string)
+            );
             continue;
         };
 
-        // .0 is negative plane, .1 is positive
-        let all_solid_axes = hit_entities
-            .iter()
-            .fold((BVec3::FALSE, BVec3::FALSE), |acc, elem| {
-                let constraints = elem.data.1;
+        let step = free.signum();
 
-                (
-                    acc.0 | constraints.neg_solid_planes,
-                    acc.0 | constraints.pos_solid_planes,
-                )
-            });
+        debug!(
+            "nudging {} out of a solid tile at {location} by {step}",
+            name.map_or("Unnamed".to_string(), ToString::to_string)
+        );
 
-        let total_vel_signs = vel.0.signum().as_ivec3();
-
-        let needs_change_x = total_vel_signs.x == 1 && all_solid_axes.0.x
-            || total_vel_signs.x == -1 && all_solid_axes.1.x;
-        let needs_change_y = total_vel_signs.y == 1 && all_solid_axes.0.y
-            || total_vel_signs.y == -1 && all_solid_axes.1.y;
-        let needs_change_z = total_vel_signs.z == 1 && all_solid_axes.0.z
-            || total_vel_signs.z == -1 && all_solid_axes.1.z;
-
-        // to make this function continuous and avoid divide by zero bugs, multiply by 1 if
-        // distance is 0. There might be a better thing to multiply but I'm not sure. Maybe 0?
-        let stopping_factor = if closest_distance.round() == 0. {
-            1.
-        } else {
-            closest_distance.round()
-        };
+        ticker.0 = Vec3::ZERO;
+        total.0 = Vec3::ZERO;
+        relative.0 = Vec3::ZERO;
+        transform.translation += tile_stretch.get_bevy(step);
+    }
+}
 
-        // FIXME: If expected to collide with entities at two locations, stopping_factor will be
-        // incorrect
-        let impulse = bvec_to_mask(BVec3::new(needs_change_x, needs_change_y, needs_change_z))
-            * bvec_to_mask(constraints.move_along)
-            * vel.0
-            / stopping_factor;
-
-        trace!("subtracting impulse {impulse}");
-        trace!("with stopping factor {stopping_factor}");
-        debug_assert!(stopping_factor.is_finite());
-        debug_assert!(impulse.is_finite());
-
-        // update collision info
-        // FIXME: make it so on_tile is per entity
-        collider.collision = Some(EntityCollision {
-            other_entities: hit_entities.iter().map(|h| h.map(|(e, _)| *e)).collect(),
-            impulse,
-        });
+/// Whether `ours` gets a tile that both it and `theirs` are moving into this update, given each
+/// one's entity and [`Weight`]. See the [module docs](self).
+#[must_use]
+pub fn wins_contested_tile(
+    ours: (Entity, Option<&Weight>),
+    theirs: (Entity, Option<&Weight>),
+) -> bool {
+    let weight = |w: Option<&Weight>| w.map_or(0., |w| w.0);
 
-        // SAFETY: we should have already returned if these queries are invalid
-        let mut vel = unsafe { total_vel_q.get_mut(entity).unwrap_unchecked() };
-        let mut r_vel = unsafe { relative_vel_q.get_mut(entity).unwrap_unchecked() };
+    match weight(ours.1).total_cmp(&weight(theirs.1)) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => ours.0.index() < theirs.0.index(),
+    }
+}
 
-        vel.0 -= impulse;
-        r_vel.0 -= impulse;
+/// How many frames until `entity` would run into a solid collider, if it kept its current
+/// velocity, or `None` if it wouldn't within `max_lookahead` frames.
+///
+/// The entity's path is stepped the same way as movement, passing through every tile on the way,
+/// against the last [`CollisionMap`], so other colliders are treated as if they stay where they
+/// are. A result of `Some(1)` means the next update will collide.
+///
+/// `None` is also returned for entities without a [`GlobalTransform`] or [`TotalVelocity`].
+///
+/// # Panics
+/// If there is no [`TileStretch`], [`CollisionMap`], [`crate::PhysicsTimestep`], or [`Time`]
+/// resource
+#[must_use]
+pub fn frames_until_collision(world: &World, entity: Entity, max_lookahead: u32) -> Option<u32> {
+    let tile_stretch = *world.resource::<TileStretch>();
+    let collision_map = world.resource::<CollisionMap>();
+    let delta_time = world
+        .resource::<crate::PhysicsTimestep>()
+        .delta_seconds(world.resource::<Time>());
 
-        // update collision info
+    let mut location = world.get::<GlobalTransform>(entity)?.location(tile_stretch);
+    let velocity = world.get::<TotalVelocity>(entity)?.0;
+    let mut ticker = world.get::<Ticker>(entity).map_or(Vec3::ZERO, |t| t.0);
+    let layers = |entity| {
+        world
+            .get::<CollisionLayers>(entity)
+            .copied()
+            .unwrap_or_default()
+    };
 
-        trace!("new vel r: {} t: {}", r_vel.0, vel.0);
+    if velocity == Vec3::ZERO {
+        return None;
     }
+
+    for frame in 1..=max_lookahead {
+        // see movement's finalize_movement
+        ticker = crate::movement::snap_ticker(ticker + velocity * delta_time);
+        let step = crate::movement::tiles_stepped(ticker);
+        ticker -= step;
+
+        let blocked = path_through(step.as_ivec3()).any(|offset| {
+            collision_map
+                .colliders_at(location + offset)
+                .any(|(_, other, constraints)| {
+                    *other != entity
+                        && constraints.violates_solidity(velocity)
+                        && layers(entity).interacts_with(&layers(*other))
+                })
+        });
+        location += step.as_ivec3();
+
+        if blocked {
+            return Some(frame);
+        }
+    }
+
+    None
 }
 
 /// Predict the change in grid location of an entity based on its current velocities. This will only be accurate
@@ -313,7 +1653,9 @@ fn calc_movement(
     // with its total velocity times the time delta to get how much it will move this frame
     //
 
-    let projected_movement_raw = total_velocity * time_delta + ticked_velocity;
+    // snapped the same way as in movement, so that the prediction matches
+    let projected_movement_raw =
+        crate::movement::snap_ticker(total_velocity * time_delta + ticked_velocity);
 
     // multiplying Signum before flooring makes sure it will floor towards zero, then we just
     // reverse it
@@ -325,6 +1667,16 @@ fn calc_movement(
     projected_movement_rounded.as_ivec3()
 }
 
+/// The offsets from its tile that an entity moving `step` in a single frame passes through, in
+/// order. Each is at most a single tile along every axis from the last, and the last is `step`
+/// itself. Empty if `step` is zero.
+fn path_through(step: IVec3) -> impl Iterator<Item = IVec3> {
+    let steps = step.abs().max_element();
+
+    // rounds each axis half away from zero, without leaving integers
+    (1..=steps).map(move |n| (step * (2 * n) + step.signum() * steps) / (2 * steps))
+}
+
 /// PERF: we could consider updating in-place
 ///
 /// TODO: I think it probably makes more sense to flatten it out to Vec<(IVec3,...)> for perf, etc
@@ -337,20 +1689,38 @@ fn build_collision_map(
         &GlobalTransform,
     )>,
     time: Res<Time>,
+    timestep: Res<crate::PhysicsTimestep>,
     tile_stretch: Res<TileStretch>,
     mut collision_map: ResMut<CollisionMap>,
 ) {
-    collision_map.0 = collider_q
-        .iter()
-        .map(|(entity, c, total_v, ticker, transform)| {
-            (
-                calc_movement(total_v, ticker, time.delta_seconds())
-                    + transform.location(*tile_stretch),
-                entity,
-                c.constraints,
-            )
-        })
-        .collect();
+    let delta_time = timestep.delta_seconds(&time);
+
+    collision_map.clear();
+    let CollisionMap {
+        predicted,
+        predicted_by_tile,
+        current_by_tile,
+        delta_seconds,
+    } = &mut *collision_map;
+    *delta_seconds = delta_time;
+
+    for (entity, c, total_v, ticker, transform) in &collider_q {
+        let location = transform.location(*tile_stretch);
+        let predicted_location = calc_movement(total_v, ticker, delta_time) + location;
+
+        // every tile a collider takes up, starting with the one its transform is on
+        for tile in c.tiles(predicted_location) {
+            predicted_by_tile
+                .entry(tile)
+                .or_default()
+                .push(predicted.len());
+            predicted.push((tile, entity, c.constraints));
+        }
+
+        for tile in c.tiles(location) {
+            current_by_tile.entry(tile).or_default().push(entity);
+        }
+    }
 }
 
 pub(super) struct Plugin;
@@ -359,11 +1729,28 @@ impl bevy_app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (build_collision_map, tile_cast_collision)
+            (
+                build_collision_map,
+                tile_cast_collision,
+                snap_to_floor,
+                update_standing_on,
+                escape_solids,
+            )
                 .chain()
                 .in_set(PhysicsSet::Collision),
         )
-        .init_resource::<CollisionMap>();
+        .add_systems(Update, bounce_on_collision.in_set(PhysicsSet::Completed))
+        .add_systems(
+            PostUpdate,
+            (update_current_overlaps, send_trigger_events)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .init_resource::<CollisionMap>()
+        .init_resource::<FloorSnap>()
+        .init_resource::<SensorOverlaps>()
+        .add_event::<EntityCollision>()
+        .add_event::<TriggerEnter>()
+        .add_event::<TriggerExit>();
     }
 }
 
@@ -381,3 +1768,52 @@ fn constraints_properly_report() {
     assert!(cant_fall_through_floor);
     assert!(!can_move_over_floor);
 }
+
+#[cfg(test)]
+#[test]
+fn slow_velocity_violates_solidity() {
+    let wall = Constraints::WALL;
+
+    // less than a tile per second should still be considered moving towards the wall
+    assert!(wall.violates_solidity(Vec3::new(0.4, 0., 0.)));
+    assert!(wall.violates_solidity(Vec3::new(0., -0.4, 0.)));
+    assert!(!wall.violates_solidity(Vec3::ZERO));
+}
+
+#[cfg(test)]
+#[test]
+fn constraints_combine_with_bitor() {
+    // a floor that also blocks moving along +x, eg. a ledge
+    let ledge = Constraints {
+        pos_solid_planes: BVec3::FALSE,
+        neg_solid_planes: BVec3::new(true, false, false),
+        move_along: BVec3::new(false, true, false),
+        one_way: false,
+    };
+    let combined = Constraints::FLOOR | ledge;
+
+    assert_eq!(combined.pos_solid_planes, BVec3::new(false, false, true));
+    assert_eq!(combined.neg_solid_planes, BVec3::new(true, false, false));
+    assert_eq!(combined.move_along, BVec3::new(false, true, false));
+    assert_eq!(Constraints::SENSOR | combined, combined);
+
+    // violated by anything either one is violated by
+    for vel in [
+        Vec3::X,
+        Vec3::NEG_X,
+        Vec3::Y,
+        Vec3::NEG_Z,
+        Vec3::Z,
+        Vec3::new(1., 0., -1.),
+        Vec3::new(-1., 1., 1.),
+    ] {
+        assert_eq!(
+            combined.violates_solidity(vel),
+            Constraints::FLOOR.violates_solidity(vel) || ledge.violates_solidity(vel),
+            "{vel}"
+        );
+    }
+    assert!(combined.violates_solidity(Vec3::X));
+    assert!(combined.violates_solidity(Vec3::NEG_Z));
+    assert!(!combined.violates_solidity(Vec3::NEG_X));
+}
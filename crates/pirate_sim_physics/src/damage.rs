@@ -0,0 +1,70 @@
+//! Damage dealt through collisions, and teams to keep entities from damaging their allies
+//!
+//! An entity with [`ContactDamage`] damages every entity with [`Health`] that its [`Collider`]
+//! collides with. If both entities are on the same [`Team`], and [`NoFriendlyFire`] is set, no
+//! damage is dealt.
+
+use bevy_app::prelude::*;
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::{Collider, PhysicsSet};
+
+/// The side an entity is on, eg. the crew of a single ship. A projectile should usually be on the
+/// same team as whoever fired it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct Team(pub u32);
+
+#[derive(Component, Debug, Clone, Copy, Deref, DerefMut, Reflect)]
+pub struct Health(pub f32);
+
+/// Damage dealt to anything with [`Health`] this entity collides with, on each frame that the
+/// collision happens.
+#[derive(Component, Debug, Clone, Copy, Deref, DerefMut, Reflect)]
+pub struct ContactDamage(pub f32);
+
+/// Whether entities on the same [`Team`] are kept from damaging each other. Defaults to true.
+#[derive(Resource, Debug, Clone, Copy, Deref, DerefMut, Reflect)]
+pub struct NoFriendlyFire(pub bool);
+
+impl Default for NoFriendlyFire {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn apply_contact_damage(
+    damager_q: Query<(&Collider, &ContactDamage, Option<&Team>)>,
+    mut health_q: Query<(&mut Health, Option<&Team>)>,
+    no_friendly_fire: Res<NoFriendlyFire>,
+) {
+    for (collider, damage, team) in &damager_q {
+        let Some(collision) = collider.collision() else {
+            continue;
+        };
+
+        for hit in &collision.other_entities {
+            let Ok((mut health, other_team)) = health_q.get_mut(hit.data) else {
+                continue;
+            };
+
+            if **no_friendly_fire && team.is_some() && team == other_team {
+                trace!("skipping friendly fire on {:?}", hit.data);
+                continue;
+            }
+
+            health.0 -= damage.0;
+        }
+    }
+}
+
+pub(super) struct Plugin;
+
+impl bevy_app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_contact_damage.in_set(PhysicsSet::Completed))
+            .init_resource::<NoFriendlyFire>();
+    }
+}
@@ -0,0 +1,134 @@
+//! Fluids, such as the sea, which slow down and float the entities inside of them
+//!
+//! Any moving entity sharing a tile with a [`Fluid`] has its velocity slowed by the fluid's drag.
+//! Weighted entities are also pushed upwards by its buoyancy, unless they are a [`DenseObject`].
+//!
+//! Everything at or below the [`SeaLevel`], if there is one, is also treated as being in the sea.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_utils::HashMap;
+
+use pirate_sim_core::tile_grid::{GetTileLocation, TileStretch};
+
+use crate::{velocity::RelativeVelocity, PhysicsSet, Weight};
+
+/// A tile of fluid. This doesn't need a collider, as fluids never block movement.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct Fluid {
+    /// Upwards velocity given to weighted entities in this fluid, in tiles per second. Anything
+    /// above [`crate::GRAVITY`] will float.
+    pub buoyancy: f32,
+    /// The fraction of an entity's velocity removed while in this fluid, from 0 to 1
+    pub drag: f32,
+}
+
+impl Fluid {
+    /// Sea water, enough to float anything with a normal weight
+    pub const SEA_WATER: Self = Self {
+        buoyancy: 12.,
+        drag: 0.5,
+    };
+}
+
+/// How high the sea comes up to. Every tile at or below it without a [`Fluid`] of its own is
+/// filled with [`Self::water`], so changing it, eg. for tides, moves where everything floats.
+///
+/// Without this resource there is no sea, only [`Fluid`] tiles.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct SeaLevel {
+    z: i32,
+    /// The fluid filling the sea
+    pub water: Fluid,
+}
+
+impl SeaLevel {
+    /// A sea of [`Fluid::SEA_WATER`] up to `z`
+    #[must_use]
+    pub fn new(z: i32) -> Self {
+        Self {
+            z,
+            water: Fluid::SEA_WATER,
+        }
+    }
+
+    /// The highest z level in the sea
+    #[must_use]
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Raise or lower the sea to `z`. Entities in the sea react on the next update
+    pub fn set(&mut self, z: i32) {
+        self.z = z;
+    }
+
+    /// Returns true if `tile` is in the sea
+    #[must_use]
+    pub fn covers(&self, tile: IVec3) -> bool {
+        tile.z <= self.z
+    }
+}
+
+/// Sinks through fluids, ignoring their buoyancy. Drag still applies. eg. an anchor
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+pub struct DenseObject;
+
+fn apply_fluids(
+    fluid_q: Query<(&Fluid, &GlobalTransform)>,
+    mut mover_q: Query<(
+        &mut RelativeVelocity,
+        &GlobalTransform,
+        Option<&Weight>,
+        Option<&DenseObject>,
+    )>,
+    tile_stretch: Res<TileStretch>,
+    sea_level: Option<Res<SeaLevel>>,
+) {
+    let fluids: HashMap<_, _> = fluid_q
+        .iter()
+        .map(|(fluid, transform)| (transform.location(*tile_stretch), *fluid))
+        .collect();
+
+    if fluids.is_empty() && sea_level.is_none() {
+        return;
+    }
+
+    for (mut relative_velocity, transform, weight, dense) in &mut mover_q {
+        let location = transform.location(*tile_stretch);
+        let sea = sea_level
+            .as_deref()
+            .filter(|sea| sea.covers(location))
+            .map(|sea| sea.water);
+
+        let Some(fluid) = fluids.get(&location).copied().or(sea) else {
+            continue;
+        };
+
+        let floats = weight.is_some_and(|w| **w != 0.) && dense.is_none();
+
+        if floats {
+            relative_velocity.0.z += fluid.buoyancy;
+        }
+
+        relative_velocity.0 *= 1. - fluid.drag.clamp(0., 1.);
+    }
+}
+
+pub(super) struct Plugin;
+
+impl bevy_app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_fluids
+                .in_set(PhysicsSet::Velocity)
+                .after(crate::velocity::calculate_relative_velocity)
+                .before(crate::movement::apply_leashes)
+                .before(crate::velocity::propagate_velocities),
+        );
+    }
+}
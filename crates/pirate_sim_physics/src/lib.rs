@@ -16,13 +16,21 @@
 use bevy_app::prelude::*;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
+use bevy_hierarchy::{DespawnRecursiveExt, Parent};
+use bevy_math::{IVec3, Vec3};
 use bevy_reflect::{prelude::*, GetTypeRegistration};
+use bevy_time::Time;
+use bevy_transform::prelude::Transform;
+
+use pirate_sim_core::tile_grid::TileStretch;
 
 pub use pirate_sim_core::PhysicsSet;
 
 pub use collision::Collider;
 
 pub mod collision;
+pub mod damage;
+pub mod fluid;
 pub mod movement;
 pub mod tile_cast;
 pub mod velocity;
@@ -35,10 +43,75 @@ mod test;
 /// The gravity constant used for weight velocity gain
 pub const GRAVITY: f32 = 9.8;
 
+/// The velocity added to any weighted entity on each physics update
+///
+/// Defaults to [`GRAVITY`] downwards along the z axis
+#[derive(Debug, Clone, Copy, Resource, Deref, DerefMut, Reflect)]
+pub struct Gravity(pub Vec3);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(Vec3::new(0., 0., -GRAVITY))
+    }
+}
+
+/// Whether or not [`Gravity`] is applied to weighted entities. Movement goals and other
+/// velocities still apply while gravity is disabled.
+#[derive(Debug, Clone, Copy, Resource, Deref, DerefMut, Reflect)]
+pub struct GravityEnabled(pub bool);
+
+impl Default for GravityEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// If set, the physics engine will advance by exactly this many seconds each update instead of
+/// the frame's delta time.
+///
+/// This only overrides the delta of each update, physics still runs once per frame. The
+/// simulation's speed against the wall clock scales with the frame rate, and anything timed with
+/// [`Time`] directly, like movement goal timeouts, drifts apart from it. Meant for deterministic
+/// tests rather than as a fixed update rate.
+#[derive(Debug, Clone, Copy, Default, Resource, Deref, DerefMut, Reflect)]
+pub struct PhysicsTimestep(pub Option<f32>);
+
+impl PhysicsTimestep {
+    /// The amount of seconds the physics engine should advance by this update
+    #[must_use]
+    #[inline]
+    pub fn delta_seconds(&self, time: &Time) -> f32 {
+        self.0.unwrap_or_else(|| time.delta_seconds())
+    }
+}
+
+/// The default [`MaxCollisionIterations`]
+pub const MAX_COLLISION_ITERATIONS: usize = 8;
+
+/// The maximum amount of times collision resolution may run per update
+///
+/// Each run after the first only resolves colliders near one whose predicted tile changed during
+/// the last run, and resolution stops early once nothing changes. A row of colliders running into
+/// a wall needs a run for each collider in the row, so anything past this may be left overlapping.
+/// A value of 1 only ever resolves each collider once.
+#[derive(Debug, Clone, Copy, Resource, Deref, DerefMut, Reflect)]
+pub struct MaxCollisionIterations(pub usize);
+
+/// Which physics subsystems may emit their verbose per-entity `trace!` logs, so that a single
+/// subsystem can be traced at a time. Everything is off by default.
+///
+/// Enabled logs are still subject to the global log level.
+#[derive(Debug, Clone, Copy, Default, Resource, Reflect)]
+pub struct PhysicsLogConfig {
+    pub trace_collision: bool,
+    pub trace_velocity: bool,
+    pub trace_movement: bool,
+}
+
 /// Any component with a weight will have gravity applied to it on each physics update
 ///
-/// Any entity with a Weight will have a velocity of [`GRAVITY`] * Weight added to its relative
-/// velocity during calculation.
+/// Any entity with a non-zero Weight will have [`Gravity`] added to its relative velocity during
+/// calculation.
 #[derive(Debug, Clone, Copy, Component, Deref, DerefMut, Reflect)]
 pub struct Weight(pub f32);
 
@@ -46,7 +119,7 @@ pub struct Weight(pub f32);
 /// transform.
 ///
 /// You must provide a transform yourself in order to get movement, in order to stay compatible
-/// with other bundles.
+/// with other bundles. Entities missing one are warned about when they are spawned.
 ///
 /// TODO: consider clearing ticker even if not attached to a Transform
 #[derive(Bundle, Debug, Default)]
@@ -55,6 +128,144 @@ pub struct PhysicsComponentBase {
     total_velocity: velocity::VelocityBundle,
 }
 
+/// What [`reset_physics_world`] should do with existing physics entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Despawn every physics entity, along with its children
+    Despawn,
+    /// Keep every physics entity where it is, but stop it by clearing its velocities, ticker, and
+    /// collision
+    ClearMotion,
+}
+
+/// Reset the physics engine to a known state, eg. between tests or on a level transition.
+///
+/// Physics entities are any entity with a [`Collider`], [`movement::Ticker`], or
+/// [`velocity::RelativeVelocity`]. The [`collision::CollisionMap`] is always cleared, and will be
+/// rebuilt on the next update.
+///
+/// Returns the amount of physics entities affected.
+pub fn reset_physics_world(world: &mut World, mode: ResetMode) -> usize {
+    let mut physics_q = world.query_filtered::<Entity, Or<(
+        With<Collider>,
+        With<movement::Ticker>,
+        With<velocity::RelativeVelocity>,
+    )>>();
+    let entities: Vec<_> = physics_q.iter(world).collect();
+
+    match mode {
+        ResetMode::Despawn => {
+            for &entity in &entities {
+                // may have already been despawned along with its parent
+                if let Some(entity) = world.get_entity_mut(entity) {
+                    entity.despawn_recursive();
+                }
+            }
+        }
+        ResetMode::ClearMotion => {
+            let mut motion_q = world.query::<(
+                Option<&mut movement::Ticker>,
+                Option<&mut velocity::RelativeVelocity>,
+                Option<&mut velocity::TotalVelocity>,
+                Option<&mut velocity::Mantained>,
+                Option<&mut Collider>,
+            )>();
+
+            for (ticker, relative, total, mantained, collider) in motion_q.iter_mut(world) {
+                if let Some(mut ticker) = ticker {
+                    ticker.0 = Vec3::ZERO;
+                }
+                if let Some(mut relative) = relative {
+                    relative.0 = Vec3::ZERO;
+                }
+                if let Some(mut total) = total {
+                    total.0 = Vec3::ZERO;
+                }
+                if let Some(mut mantained) = mantained {
+                    mantained.0 = Vec3::ZERO;
+                }
+                if let Some(mut collider) = collider {
+                    collider.clear_collision();
+                }
+            }
+        }
+    }
+
+    if let Some(mut collision_map) = world.get_resource_mut::<collision::CollisionMap>() {
+        collision_map.clear();
+    }
+
+    entities.len()
+}
+
+/// A compact snapshot of a single entity's physics state, eg. for networking or rollback.
+///
+/// `tile` is taken from the entity's [`Transform`], so it is relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct PhysicsState {
+    pub tile: IVec3,
+    pub ticker: Vec3,
+    /// The entity's [`velocity::TotalVelocity`]
+    pub velocity: Vec3,
+}
+
+impl PhysicsState {
+    /// Snapshot `entity`, or `None` if it doesn't have a [`Transform`], [`movement::Ticker`], and
+    /// [`velocity::TotalVelocity`]
+    ///
+    /// # Panics
+    /// If there is no [`TileStretch`] resource
+    #[must_use]
+    pub fn extract(world: &World, entity: Entity) -> Option<Self> {
+        let tile_stretch = *world.resource::<TileStretch>();
+
+        Some(Self {
+            tile: tile_stretch.get_closest(world.get::<Transform>(entity)?.translation),
+            ticker: world.get::<movement::Ticker>(entity)?.0,
+            velocity: world.get::<velocity::TotalVelocity>(entity)?.0,
+        })
+    }
+
+    /// Move `entity` back to this snapshot, returning false if it is missing any of the
+    /// components needed by [`PhysicsState::extract`].
+    ///
+    /// Its [`velocity::RelativeVelocity`] is also set, so that the velocity survives the next
+    /// propagation. It takes its parent's velocity into account, as of the parent's last update.
+    ///
+    /// # Panics
+    /// If there is no [`TileStretch`] resource
+    pub fn apply(&self, world: &mut World, entity: Entity) -> bool {
+        let tile_stretch = *world.resource::<TileStretch>();
+
+        let parent_velocity = world
+            .get::<Parent>(entity)
+            .and_then(|parent| world.get::<velocity::TotalVelocity>(parent.get()))
+            .map_or(Vec3::ZERO, |total| total.0);
+
+        let Some((mut transform, mut ticker, mut total)) = world
+            .query::<(
+                &mut Transform,
+                &mut movement::Ticker,
+                &mut velocity::TotalVelocity,
+            )>()
+            .get_mut(world, entity)
+            .ok()
+        else {
+            return false;
+        };
+
+        transform.translation = tile_stretch.get_bevy(self.tile);
+        ticker.0 = self.ticker;
+        total.0 = self.velocity;
+
+        if let Some(mut relative) = world.get_mut::<velocity::RelativeVelocity>(entity) {
+            relative.0 = self.velocity - parent_velocity;
+        }
+
+        true
+    }
+}
+
 fn startup(type_registry: Res<AppTypeRegistry>, mut commands: Commands) {
     // register raycast command
     #[cfg(feature = "developer-tools")]
@@ -64,21 +275,78 @@ fn startup(type_registry: Res<AppTypeRegistry>, mut commands: Commands) {
             tile_cast::console::raycast_console,
         ),
     );
+    #[cfg(feature = "developer-tools")]
+    commands.add(
+        pirate_sim_console::registration::RegisterConsoleCommand::new(
+            "raycast_from".into(),
+            tile_cast::console::raycast_from_console,
+        ),
+    );
+    #[cfg(feature = "developer-tools")]
+    commands.add(
+        pirate_sim_console::registration::RegisterConsoleCommand::new(
+            "teleport".into(),
+            movement::console::teleport_console,
+        ),
+    );
+    #[cfg(feature = "developer-tools")]
+    commands.add(
+        pirate_sim_console::registration::RegisterConsoleCommand::new(
+            "gravity".into(),
+            velocity::console::gravity_console,
+        ),
+    );
+    #[cfg(feature = "developer-tools")]
+    commands.add(
+        pirate_sim_console::registration::RegisterConsoleCommand::new(
+            "weight".into(),
+            velocity::console::weight_console,
+        ),
+    );
 
     let mut type_registry_w = type_registry.write();
 
     type_registry_w.add_registration(movement::Ticker::get_type_registration());
+    type_registry_w.add_registration(movement::Leash::get_type_registration());
+    type_registry_w.add_registration(movement::Facing::get_type_registration());
+    type_registry_w.add_registration(movement::WorldBounds::get_type_registration());
+    type_registry_w.add_registration(movement::DespawnOutOfBounds::get_type_registration());
+    type_registry_w.add_registration(movement::EnforceGridAlignment::get_type_registration());
+    type_registry_w.add_registration(movement::LastTile::get_type_registration());
+    type_registry_w.add_registration(movement::PhysicsFrozen::get_type_registration());
+    type_registry_w.add_registration(movement::OscillationWatchdog::get_type_registration());
     type_registry_w.add_registration(velocity::RelativeVelocity::get_type_registration());
     type_registry_w.add_registration(velocity::Mantained::get_type_registration());
+    type_registry_w.add_registration(velocity::Friction::get_type_registration());
+    type_registry_w.add_registration(velocity::Acceleration::get_type_registration());
     type_registry_w.add_registration(velocity::TotalVelocity::get_type_registration());
     type_registry_w.add_registration(velocity::LastTotal::get_type_registration());
     type_registry_w.add_registration(velocity::LastRelative::get_type_registration());
     type_registry_w.add_registration(velocity::FromGround::get_type_registration());
     type_registry_w.add_registration(collision::Constraints::get_type_registration());
     type_registry_w.add_registration(collision::Collider::get_type_registration());
+    type_registry_w.add_registration(collision::CollisionLayers::get_type_registration());
     type_registry_w.add_registration(collision::CollisionMap::get_type_registration());
+    type_registry_w.add_registration(collision::FloorSnap::get_type_registration());
+    type_registry_w.add_registration(collision::StandingOn::get_type_registration());
+    type_registry_w.add_registration(collision::Passthrough::get_type_registration());
+    type_registry_w.add_registration(collision::CurrentOverlaps::get_type_registration());
+    type_registry_w.add_registration(collision::BounceOnCollision::get_type_registration());
+    type_registry_w.add_registration(collision::Restitution::get_type_registration());
+    type_registry_w.add_registration(damage::Team::get_type_registration());
+    type_registry_w.add_registration(damage::Health::get_type_registration());
+    type_registry_w.add_registration(damage::ContactDamage::get_type_registration());
+    type_registry_w.add_registration(damage::NoFriendlyFire::get_type_registration());
+    type_registry_w.add_registration(fluid::Fluid::get_type_registration());
+    type_registry_w.add_registration(fluid::DenseObject::get_type_registration());
+    type_registry_w.add_registration(fluid::SeaLevel::get_type_registration());
     type_registry_w.add_registration(MovementGoal::get_type_registration());
     type_registry_w.add_registration(Weight::get_type_registration());
+    type_registry_w.add_registration(Gravity::get_type_registration());
+    type_registry_w.add_registration(GravityEnabled::get_type_registration());
+    type_registry_w.add_registration(PhysicsTimestep::get_type_registration());
+    type_registry_w.add_registration(MaxCollisionIterations::get_type_registration());
+    type_registry_w.add_registration(PhysicsLogConfig::get_type_registration());
 }
 
 /// A plugin to setup essential physics systems
@@ -88,16 +356,46 @@ fn startup(type_registry: Res<AppTypeRegistry>, mut commands: Commands) {
 ///
 /// Any systems that want to affect the physics engine in a given frame must run before
 /// [`PhysicsSet::Velocity`].
-pub struct PhysicsPlugin;
+///
+/// Each field is inserted as its corresponding resource, which can also be changed at runtime.
+pub struct PhysicsPlugin {
+    /// See [`Gravity`]
+    pub gravity: Vec3,
+    /// See [`PhysicsTimestep`]
+    pub delta_override: Option<f32>,
+    /// See [`MaxCollisionIterations`]
+    pub max_iterations: usize,
+}
+
+impl Default for PhysicsPlugin {
+    fn default() -> Self {
+        Self {
+            gravity: Gravity::default().0,
+            delta_override: None,
+            max_iterations: MAX_COLLISION_ITERATIONS,
+        }
+    }
+}
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.configure_set(Update, PhysicsSet::Input.before(PhysicsSet::Velocity))
+        app.insert_resource(Gravity(self.gravity))
+            .init_resource::<GravityEnabled>()
+            .init_resource::<PhysicsLogConfig>()
+            .insert_resource(PhysicsTimestep(self.delta_override))
+            .insert_resource(MaxCollisionIterations(self.max_iterations))
+            .configure_set(Update, PhysicsSet::Input.before(PhysicsSet::Velocity))
             .configure_set(Update, PhysicsSet::Velocity.before(PhysicsSet::Collision))
             .configure_set(Update, PhysicsSet::Collision.after(PhysicsSet::Velocity))
             .configure_set(Update, PhysicsSet::Movement.after(PhysicsSet::Collision))
             .configure_set(Update, PhysicsSet::Completed.after(PhysicsSet::Movement))
-            .add_plugins((velocity::Plugin, collision::Plugin, movement::Plugin))
+            .add_plugins((
+                velocity::Plugin,
+                collision::Plugin,
+                movement::Plugin,
+                damage::Plugin,
+                fluid::Plugin,
+            ))
             .add_systems(Startup, startup);
     }
 }
@@ -2,13 +2,18 @@ use bevy_app::prelude::*;
 use bevy_core::Name;
 use bevy_derive::Deref;
 use bevy_ecs::prelude::*;
+use bevy_hierarchy::DespawnRecursiveExt;
 use bevy_log::prelude::*;
 use bevy_math::prelude::*;
 use bevy_reflect::prelude::*;
 use bevy_time::Time;
-use bevy_transform::prelude::*;
+use bevy_transform::{prelude::*, TransformSystem};
 
-use pirate_sim_core::{tile_grid::TileStretch, utils::bvec_to_mask};
+use pirate_sim_core::{
+    goals::MovementGoal,
+    tile_grid::{GetTileLocation, TileChanged, TileRegion, TileStretch},
+    utils::{self, bvec_to_mask},
+};
 
 use super::PhysicsSet;
 
@@ -21,76 +26,408 @@ use super::PhysicsSet;
 /// Currently if a component has 0 velocity, its ticker will be reset to 0,0,0. In the future this
 /// should be changed so that you can reset your ticker trough a request like RequestResetTicker.
 #[derive(Debug, Component, Clone, Copy, Default, Deref, Reflect)]
-pub struct Ticker(Vec3);
+pub struct Ticker(pub(crate) Vec3);
+
+/// Round any axis of `ticker` within [`utils::TOLERANCE`] of a whole tile onto that tile.
+///
+/// Float error can otherwise leave a ticker just short of a whole tile, eg. 60 frames of 5/60
+/// adding up to 4.99999, which would lose a tile of movement.
+#[must_use]
+pub(crate) fn snap_ticker(ticker: Vec3) -> Vec3 {
+    let rounded = ticker.round();
+    let near_whole = (ticker - rounded)
+        .abs()
+        .cmple(Vec3::splat(utils::TOLERANCE));
+
+    Vec3::select(near_whole, rounded, ticker)
+}
+
+/// The whole tiles `ticker` has made along each axis, towards zero. eg. `(0.5, -1.5, 3.)` steps
+/// `(0, -1, 3)`
+#[must_use]
+pub(crate) fn tiles_stepped(ticker: Vec3) -> Vec3 {
+    ticker.trunc()
+}
 
 /// Apply, applies any tickers that have moved at least one tile. This is essentially flushing the
 /// MovementTicker buffer.
 ///
+/// Every whole tile the ticker has made is moved, and only the fraction of a tile left over is
+/// kept, so an entity covers the same tiles per second no matter the frame rate. Collision keeps
+/// entities from moving through anything solid on the way.
+///
 /// This will reset any tickers with a TotalVelocity of 0 to 0,0,0. This may lead to bugs in the
 /// future
 fn finalize_movement(
-    mut phsyics_components: Query<(
-        &mut Transform,
-        &mut Ticker,
-        &super::velocity::RelativeVelocity,
-        Option<&Name>,
-    )>,
+    mut phsyics_components: Query<
+        (
+            &mut Transform,
+            &mut Ticker,
+            &super::velocity::RelativeVelocity,
+            Option<&Name>,
+        ),
+        Without<PhysicsFrozen>,
+    >,
     tile_stretch: Res<TileStretch>,
     time: Res<Time>,
+    timestep: Res<crate::PhysicsTimestep>,
+    log_config: Res<crate::PhysicsLogConfig>,
 ) {
     // this will make it so entities only move a tile once an entire tiles worth of movement
     // has been "made", keeping it in a grid based system
     //
     // also converts from grid to tile_stretch
 
-    let delta_time = time.delta_seconds();
+    let delta_time = timestep.delta_seconds(&time);
+
+    let stretch = Vec3::new(f32::from(tile_stretch.x), f32::from(tile_stretch.y), 1.);
 
     for (mut transform, mut ticker, relative_velocity, name) in phsyics_components.iter_mut() {
-        // update ticker, only apply velocity * delta to keep time consistent
-        ticker.0 += **relative_velocity * delta_time;
+        #[cfg(debug_assertions)]
+        let (start, prev_ticker) = (transform.translation, ticker.0);
 
-        let z_sign = ticker.z.signum();
-        let y_sign = ticker.y.signum();
-        let x_sign = ticker.x.signum();
-        let mut moved = false;
+        // only apply velocity * delta to keep time consistent
+        let made = **relative_velocity * delta_time;
+        ticker.0 = snap_ticker(ticker.0 + made);
 
-        while ticker.z * z_sign >= 1. {
-            moved = true;
+        // This must match the prediction in collision's `calc_movement`
+        let step = tiles_stepped(ticker.0);
+        let moved = step != Vec3::ZERO;
 
-            transform.translation.z += z_sign;
-            ticker.0.z -= 1. * z_sign;
+        ticker.0 -= step;
+        debug_assert!(ticker.is_finite());
+        debug_assert!(
+            ticker.abs().cmplt(Vec3::ONE).all(),
+            "a whole tile was left in the ticker {}",
+            ticker.0
+        );
+
+        transform.translation += step * stretch;
 
-            debug_assert!(ticker.z.is_finite() && !ticker.z.is_nan());
-            assert!(ticker.z.signum() == z_sign);
+        // no movement should be lost or made up by the flush; the tiles moved plus what's left in
+        // the ticker has to be what was in the ticker plus what was made this frame
+        #[cfg(debug_assertions)]
+        {
+            let moved_tiles = ((transform.translation - start) / stretch).round();
+            debug_assert!(
+                utils::approx_eq_vec3(moved_tiles + ticker.0, prev_ticker + made, utils::TOLERANCE),
+                "{} moved {moved_tiles} tiles leaving {} in its ticker, but had {} to move",
+                name.map_or("Unnamed".to_string(), ToString::to_string),
+                ticker.0,
+                prev_ticker + made,
+            );
         }
-        while ticker.y * y_sign >= 1. {
-            moved = true;
 
-            transform.translation.y += tile_stretch.0 as f32 * y_sign;
-            ticker.0.y -= 1. * y_sign;
+        if log_config.trace_movement {
+            trace!(
+                "{} moved: {}",
+                name.map_or("Unnamed".to_string(), ToString::to_string),
+                moved,
+            );
+        }
+    }
+}
+
+/// The last tile an entity was seen on, used to send [`TileChanged`] events. Part of
+/// [`MovementBundle`].
+///
+/// `None` until the entity's first tile is recorded, which doesn't send an event.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct LastTile(pub Option<IVec3>);
 
-            debug_assert!(ticker.y.is_finite() && !ticker.y.is_nan());
-            assert!(ticker.z.signum() == z_sign);
+/// Send a [`TileChanged`] for every entity that moved to a new tile this frame. Runs after
+/// transform propagation, so that movement from parents is included.
+pub(crate) fn send_tile_changes(
+    mut tracked_q: Query<(Entity, &GlobalTransform, &mut LastTile), Changed<GlobalTransform>>,
+    mut tile_changed: EventWriter<TileChanged>,
+    tile_stretch: Res<TileStretch>,
+) {
+    for (entity, transform, mut last_tile) in &mut tracked_q {
+        let to = transform.location(*tile_stretch);
+
+        match last_tile.0 {
+            Some(from) if from == to => continue,
+            Some(from) => tile_changed.send(TileChanged { entity, from, to }),
+            None => {}
         }
-        while ticker.0.x * x_sign >= 1. {
-            moved = true;
 
-            transform.translation.x += tile_stretch.1 as f32 * x_sign;
-            ticker.0.x -= 1. * x_sign;
+        last_tile.0 = Some(to);
+    }
+}
+
+/// Warn whenever this entity flips back and forth between the same two tiles for `frames` frames
+/// in a row, which is usually a sign of collision resolution fighting with something else over
+/// where it should be. A debugging aid, add it to any entity you suspect.
+///
+/// Each warning also sends an [`Oscillating`] event.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct OscillationWatchdog {
+    /// How many frames in a row the entity must move back to the tile it was on two frames ago
+    /// before it is warned about
+    pub frames: u32,
+    two_ago: Option<IVec3>,
+    last: Option<IVec3>,
+    alternations: u32,
+}
 
-            debug_assert!(ticker.x.is_finite() && !ticker.x.is_nan());
-            assert!(ticker.z.signum() == z_sign);
+impl OscillationWatchdog {
+    #[must_use]
+    pub fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            two_ago: None,
+            last: None,
+            alternations: 0,
         }
+    }
 
-        trace!(
-            "{} moved: {}",
-            name.map_or("Unnamed".to_string(), ToString::to_string),
-            moved,
-        );
+    /// Returns true if the entity has been oscillating for at least [`Self::frames`] frames
+    #[must_use]
+    pub fn is_oscillating(&self) -> bool {
+        self.frames > 0 && self.alternations >= self.frames
+    }
+}
+
+impl Default for OscillationWatchdog {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
+
+/// Sent once each time an entity with an [`OscillationWatchdog`] starts oscillating
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Oscillating {
+    pub entity: Entity,
+    pub between: (IVec3, IVec3),
+}
+
+fn watch_for_oscillation(
+    mut watched_q: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut OscillationWatchdog,
+        Option<&Name>,
+    )>,
+    mut oscillating: EventWriter<Oscillating>,
+    tile_stretch: Res<TileStretch>,
+) {
+    for (entity, transform, mut watchdog, name) in &mut watched_q {
+        let tile = transform.location(*tile_stretch);
+        let was_oscillating = watchdog.is_oscillating();
+
+        let alternated = watchdog.two_ago == Some(tile) && watchdog.last != Some(tile);
+        watchdog.alternations = if alternated {
+            watchdog.alternations.saturating_add(1)
+        } else {
+            0
+        };
+        watchdog.two_ago = watchdog.last;
+        watchdog.last = Some(tile);
+
+        if watchdog.is_oscillating() && !was_oscillating {
+            let other = watchdog.two_ago.unwrap_or(tile);
+
+            warn!(
+                "{} has been oscillating between {tile} and {other} for {} frames",
+                name.map_or("Unnamed".to_string(), ToString::to_string),
+                watchdog.alternations,
+            );
+            oscillating.send(Oscillating {
+                entity,
+                between: (tile, other),
+            });
+        }
     }
 }
 
-/// clear tickers when velocity is lessened
+/// Snap this entity's [`Transform`] back onto exact tile multiples whenever it is stationary.
+///
+/// Long-lived entities can slowly drift off grid through float error, even while they aren't
+/// moving, which will eventually trip up [`TileStretch::get_tile`].
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct EnforceGridAlignment;
+
+fn enforce_grid_alignment(
+    mut aligned_q: Query<
+        (&mut Transform, Option<&super::velocity::TotalVelocity>),
+        With<EnforceGridAlignment>,
+    >,
+    tile_stretch: Res<TileStretch>,
+) {
+    let stretch = Vec3::new(f32::from(tile_stretch.x), f32::from(tile_stretch.y), 1.);
+
+    for (mut transform, total_velocity) in &mut aligned_q {
+        if total_velocity.is_some_and(|v| v.0 != Vec3::ZERO) {
+            continue;
+        }
+
+        // rounded instead of using `get_closest`, which floors, so that drifting just below a
+        // tile still snaps up to it
+        let snapped = (transform.translation / stretch).round() * stretch;
+
+        // avoid triggering change detection every frame
+        if transform.translation != snapped {
+            transform.translation = snapped;
+        }
+    }
+}
+
+/// The direction an entity is facing along x and y, taken from the last direction it tried to
+/// move in. Each axis is -1, 0, or 1.
+///
+/// Defaults to facing down, along -y.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Deref, Reflect)]
+pub struct Facing(pub IVec3);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(IVec3::NEG_Y)
+    }
+}
+
+impl Facing {
+    /// The tile directly in front of an entity at `transform`, eg. for interaction prompts
+    #[must_use]
+    pub fn facing_tile(&self, transform: &GlobalTransform, tile_stretch: TileStretch) -> IVec3 {
+        transform.location(tile_stretch) + self.0
+    }
+}
+
+fn update_facing(mut facing_q: Query<(&mut Facing, &super::velocity::RelativeVelocity)>) {
+    for (mut facing, relative_velocity) in &mut facing_q {
+        // falling, or being pushed up shouldn't turn an entity around
+        let direction = relative_velocity.0 * Vec3::new(1., 1., 0.);
+
+        if direction == Vec3::ZERO {
+            continue;
+        }
+
+        let new_facing =
+            Vec3::select(direction.cmpeq(Vec3::ZERO), Vec3::ZERO, direction.signum()).as_ivec3();
+
+        // avoid triggering change detection every frame
+        if facing.0 != new_facing {
+            facing.0 = new_facing;
+        }
+    }
+}
+
+/// Keeps an entity inside of a [`TileRegion`], eg. to keep AI on its own ship.
+///
+/// Once a leashed entity reaches the edge of its region, any part of its [`MovementGoal`] and
+/// [`RelativeVelocity`](super::velocity::RelativeVelocity) heading out of the region is removed,
+/// while it is still free to move back inwards.
+#[derive(Component, Debug, Clone, Copy, Deref, Reflect)]
+pub struct Leash(pub TileRegion);
+
+pub(crate) fn apply_leashes(
+    mut leashed_q: Query<(
+        &Leash,
+        &GlobalTransform,
+        Option<&mut MovementGoal>,
+        &mut super::velocity::RelativeVelocity,
+    )>,
+    tile_stretch: Res<TileStretch>,
+) {
+    for (leash, transform, goal, mut relative_velocity) in &mut leashed_q {
+        let location = transform.location(*tile_stretch);
+
+        let at_max = location.cmpge(leash.max);
+        let at_min = location.cmple(leash.min);
+
+        let keep_axes = |vel: Vec3| {
+            let outward = at_max & vel.cmpgt(Vec3::ZERO) | at_min & vel.cmplt(Vec3::ZERO);
+
+            vel * bvec_to_mask(!outward)
+        };
+
+        if let Some(mut goal) = goal {
+            let kept = keep_axes(goal.0);
+
+            // avoid triggering change detection every frame
+            if kept != goal.0 {
+                goal.0 = kept;
+            }
+        }
+
+        relative_velocity.0 = keep_axes(relative_velocity.0);
+    }
+}
+
+/// Freeze this entity in place while the rest of the world keeps simulating, eg. a boss during a
+/// cutscene.
+///
+/// Its own velocity is treated as zero and its [`Ticker`] stops advancing, but its
+/// [`MovementGoal`] and other velocity sources are left alone, so it carries on as before once
+/// unfrozen. It still moves along with its parent.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct PhysicsFrozen;
+
+fn freeze_velocities(
+    mut frozen_q: Query<&mut super::velocity::RelativeVelocity, With<PhysicsFrozen>>,
+) {
+    for mut relative_velocity in &mut frozen_q {
+        relative_velocity.0 = Vec3::ZERO;
+    }
+}
+
+/// The region of tiles that moving entities are kept inside of. Moving entities that leave it are
+/// clamped back to its edge, or despawned if they are marked with [`DespawnOutOfBounds`].
+///
+/// If this resource doesn't exist, entities are free to move anywhere.
+#[derive(Resource, Debug, Clone, Copy, Deref, Reflect)]
+pub struct WorldBounds(pub TileRegion);
+
+/// Despawn this entity, and its children, once it leaves the [`WorldBounds`] instead of clamping
+/// it, eg. for projectiles fired off into empty space.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+pub struct DespawnOutOfBounds;
+
+fn enforce_world_bounds(
+    mut commands: Commands,
+    mut moving_q: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            Option<&DespawnOutOfBounds>,
+            Option<&Name>,
+        ),
+        With<Ticker>,
+    >,
+    world_bounds: Option<Res<WorldBounds>>,
+    tile_stretch: Res<TileStretch>,
+    log_config: Res<crate::PhysicsLogConfig>,
+) {
+    let Some(world_bounds) = world_bounds else {
+        return;
+    };
+
+    for (entity, mut transform, global_transform, despawn, name) in &mut moving_q {
+        let location = global_transform.location(*tile_stretch);
+
+        if world_bounds.contains(location) {
+            continue;
+        }
+
+        let name = name.map_or("Unnamed".to_string(), ToString::to_string);
+
+        if despawn.is_some() {
+            debug!("Despawning {name}, which left the world at {location}");
+            commands.entity(entity).despawn_recursive();
+        } else {
+            if log_config.trace_movement {
+                trace!("Clamping {name}, which left the world at {location}");
+            }
+            let clamped = location.clamp(world_bounds.min, world_bounds.max);
+            transform.translation += tile_stretch.get_bevy(clamped - location);
+        }
+    }
+}
+
+/// clear tickers along any axis velocity stopped or turned around on, so that movement buffered up
+/// going one way isn't spent going the other. Slowing down keeps the ticker, eg. for a bounce
+/// that slows a little every frame
 fn clear_tickers(
     mut ticker_q: Query<
         (
@@ -101,33 +438,144 @@ fn clear_tickers(
         ),
         Changed<crate::velocity::RelativeVelocity>,
     >,
+    log_config: Res<crate::PhysicsLogConfig>,
 ) {
     ticker_q.for_each_mut(|(mut t, rv, lrv, name)| {
-        // acceleration = change in v = current vel - last vel. Approximates a derivative
-        let acceleration = **rv - **lrv;
-
-        // will be true if vel has not slowed down on that individual axis
-        let mask = acceleration
-            .as_ivec3()
-            .signum()
-            .cmpeq(lrv.as_ivec3().signum())
-            | acceleration.cmpeq(Vec3::ZERO);
-
-        trace!(
-            "Clearing ticker for entity {} with mask {}",
-            name.map_or("Unnamed".to_string(), ToString::to_string),
-            mask
-        );
+        // will be true if vel is still going the same way on that individual axis
+        let mask = rv.signum().cmpeq(lrv.signum()) & rv.cmpne(Vec3::ZERO);
+
+        if log_config.trace_movement {
+            trace!(
+                "Clearing ticker for entity {} with mask {}",
+                name.map_or("Unnamed".to_string(), ToString::to_string),
+                mask
+            );
+        }
 
         t.0 *= bvec_to_mask(mask);
     });
 }
 
+/// Find any newly added physics entities without a [`Transform`]. [`MovementBundle`] leaves the
+/// transform up to the user, and forgetting it means the entity will silently never move.
+///
+/// Pipe into [`warn_missing_transforms`] to report them.
+fn find_missing_transforms(
+    missing_q: Query<
+        Entity,
+        (
+            Or<(Added<Ticker>, Added<super::velocity::RelativeVelocity>)>,
+            Without<Transform>,
+        ),
+    >,
+) -> Vec<Entity> {
+    missing_q.iter().collect()
+}
+
+fn warn_missing_transforms(In(missing): In<Vec<Entity>>, name_q: Query<&Name>) {
+    for entity in missing {
+        warn!(
+            "{} ({entity:?}) has physics components but no Transform, so it will never move",
+            name_q
+                .get(entity)
+                .map_or("Unnamed".to_string(), ToString::to_string)
+        );
+    }
+}
+
 /// A bundle allowing an entity to be moved by the physics system
 #[derive(Bundle, Default)]
 pub struct MovementBundle {
     velocity_bundle: super::velocity::VelocityBundle,
     ticker: Ticker,
+    facing: Facing,
+    last_tile: LastTile,
+}
+
+#[cfg(feature = "developer-tools")]
+pub(super) mod console {
+    use bevy_ecs::{prelude::*, system::Command};
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use pirate_sim_console::{self as console, Output, PrintStringCommand};
+    use pirate_sim_core::{goals::MovementGoal, tile_grid::TileStretch};
+    use std::{collections::VecDeque, error::Error};
+
+    use super::Ticker;
+    use crate::velocity::{Mantained, RelativeVelocity, TotalVelocity};
+
+    pub fn teleport_console(input: VecDeque<console::Token>, commands: &mut Commands) {
+        // teleport name|@selected x y z
+
+        if input.len() == 4 {
+            let to_result = || -> Result<_, Box<dyn Error>> {
+                let x: i32 = input[1].string.parse()?;
+                let y: i32 = input[2].string.parse()?;
+                let z: i32 = input[3].string.parse()?;
+
+                Ok(IVec3::new(x, y, z))
+            }();
+
+            match to_result {
+                Ok(to) => commands.add(TeleportCommand {
+                    name: input[0].string.clone(),
+                    to,
+                }),
+                Err(e) => commands.add(PrintStringCommand(format!(
+                    "Invalid arguments: error `{e}`",
+                ))),
+            }
+        } else {
+            commands.add(PrintStringCommand(format!(
+                "Incorrect length: expected 4 arguments but was given {}",
+                input.len()
+            )));
+        }
+    }
+
+    /// Move an entity to a tile, and clear anything that would keep it moving afterwards
+    pub(crate) struct TeleportCommand {
+        pub name: String,
+        pub to: IVec3,
+    }
+
+    impl Command for TeleportCommand {
+        fn apply(self, world: &mut World) {
+            let output = match console::find_target(world, &self.name) {
+                Some(entity) => {
+                    let tile_stretch = *world
+                        .get_resource::<TileStretch>()
+                        .expect("No tile stretch initialized??");
+                    let mut entity_mut = world.entity_mut(entity);
+
+                    if let Some(mut transform) = entity_mut.get_mut::<Transform>() {
+                        transform.translation = tile_stretch.get_bevy(self.to);
+                    }
+                    if let Some(mut ticker) = entity_mut.get_mut::<Ticker>() {
+                        *ticker = Ticker::default();
+                    }
+                    if let Some(mut goal) = entity_mut.get_mut::<MovementGoal>() {
+                        goal.0 = Vec3::ZERO;
+                    }
+                    if let Some(mut mantained) = entity_mut.get_mut::<Mantained>() {
+                        mantained.0 = Vec3::ZERO;
+                    }
+                    if let Some(mut relative) = entity_mut.get_mut::<RelativeVelocity>() {
+                        relative.0 = Vec3::ZERO;
+                    }
+                    if let Some(mut total) = entity_mut.get_mut::<TotalVelocity>() {
+                        total.0 = Vec3::ZERO;
+                    }
+
+                    format!("Teleported {} to {}", self.name, self.to)
+                }
+                None => "Could not find entity".into(),
+            };
+
+            world.send_event(Output::String(output));
+            world.send_event(Output::End);
+        }
+    }
 }
 
 pub(super) struct Plugin;
@@ -144,7 +592,29 @@ impl bevy_app::Plugin for Plugin {
                 clear_tickers
                     .after(PhysicsSet::Velocity)
                     .before(PhysicsSet::Collision),
+                apply_leashes
+                    .in_set(PhysicsSet::Velocity)
+                    .after(crate::velocity::calculate_relative_velocity)
+                    .before(crate::velocity::propagate_velocities),
+                freeze_velocities
+                    .in_set(PhysicsSet::Velocity)
+                    .after(apply_leashes)
+                    .before(crate::velocity::propagate_velocities),
+                update_facing
+                    .in_set(PhysicsSet::Velocity)
+                    .after(freeze_velocities),
+                enforce_world_bounds.in_set(PhysicsSet::Completed),
+                enforce_grid_alignment.in_set(PhysicsSet::Completed),
+                find_missing_transforms
+                    .pipe(warn_missing_transforms)
+                    .before(PhysicsSet::Velocity),
             ),
-        );
+        )
+        .add_systems(
+            PostUpdate,
+            (send_tile_changes, watch_for_oscillation).after(TransformSystem::TransformPropagate),
+        )
+        .add_event::<TileChanged>()
+        .add_event::<Oscillating>();
     }
 }
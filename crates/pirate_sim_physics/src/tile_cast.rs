@@ -2,7 +2,11 @@ use bevy_log::trace;
 use bevy_math::prelude::*;
 
 use bevy_reflect::Reflect;
-use pirate_sim_core::tile_grid::{GetTileLocation, TileStretch};
+use bevy_utils::HashMap;
+use pirate_sim_core::{
+    tile_grid::{GetTileLocation, TileStretch},
+    utils,
+};
 
 #[derive(Debug, Clone, Copy, Reflect)]
 pub struct Hit<Data> {
@@ -12,6 +16,9 @@ pub struct Hit<Data> {
     pub offset: IVec3,
     /// the distance from Origin + Ticker
     pub distance: f32,
+    /// The face of the tile the ray entered through, pointing back out towards the ray. eg.
+    /// [`IVec3::NEG_X`] for a ray moving along +x. Zero for hits on the origin's own tile.
+    pub normal: IVec3,
     /// The data passed in from the original iterator
     pub data: Data,
 }
@@ -23,9 +30,21 @@ impl<Data> Hit<Data> {
             offset: self.offset,
             translation: self.translation,
             distance: self.distance,
+            normal: self.normal,
             data: f(self.data),
         }
     }
+
+    /// Order hits closest first, breaking ties by translation along x, then y, then z so that the
+    /// order doesn't depend on the order things were cast against
+    #[must_use]
+    pub fn cmp_distance<Other>(&self, other: &Hit<Other>) -> std::cmp::Ordering {
+        self.distance.total_cmp(&other.distance).then_with(|| {
+            self.translation
+                .to_array()
+                .cmp(&other.translation.to_array())
+        })
+    }
 }
 
 /// The origin of a tile-cast
@@ -35,6 +54,25 @@ pub struct Origin {
     pub ticker: Vec3,
 }
 
+/// The normal of the face a ray from `ray_origin` along `direction` enters the tile centred on
+/// `tile` through. This is the axis whose near face the ray reaches last.
+///
+/// A ray passing exactly through an edge or corner counts as entering through z, then y, then x.
+fn entry_normal(ray_origin: Vec3, direction: Vec3, tile: Vec3) -> IVec3 {
+    (0..3)
+        .filter(|&axis| direction[axis] != 0.)
+        .map(|axis| {
+            let near_face = tile[axis] - direction[axis].signum() * 0.5;
+            (axis, (near_face - ray_origin[axis]) / direction[axis])
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(IVec3::ZERO, |(axis, _)| {
+            let mut normal = IVec3::ZERO;
+            normal[axis] = if direction[axis] > 0. { -1 } else { 1 };
+            normal
+        })
+}
+
 /// Raycast from `start_translation` with velocity of `ray_vel`
 ///
 /// Takes an iterator over any tuple `(A, impl [GetTileLocation])` and returns a hit containing the
@@ -42,16 +80,47 @@ pub struct Origin {
 ///
 /// If `include_origin` is true, then it will return any T in the
 /// same grid as `start_translation`, and it is your responsibility to filter out unwanted entities,
-/// ie if you're casting out from a specific entity.
+/// ie if you're casting out from a specific entity. If it is false, anything sharing the origin's
+/// tile will be skipped.
+///
+/// A `ray_vel` of zero has no direction, so only the origin's tile can be hit.
 ///
 /// It currently rounds the ray onto the grid, which while being accurate in a tile-based physics
 /// context, may lead to surprising results
+///
+/// Nothing is traced; use [`tile_cast_traced`] to trace each candidate checked.
 #[inline]
 #[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
 pub fn tile_cast<Data, Location>(
     origin: Origin,
     ray_vel: Vec3,
     tile_stretch: TileStretch,
+    include_origin: bool,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+) -> impl Iterator<Item = Hit<Data>>
+where
+    Location: GetTileLocation,
+{
+    tile_cast_traced(
+        origin,
+        ray_vel,
+        tile_stretch,
+        include_origin,
+        false,
+        entity_pool,
+    )
+}
+
+/// [`tile_cast`], but emitting a `trace!` for the ray and every candidate checked against it if
+/// `tracing` is true, eg. from a [`PhysicsLogConfig`](crate::PhysicsLogConfig) flag.
+#[inline]
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn tile_cast_traced<Data, Location>(
+    origin: Origin,
+    ray_vel: Vec3,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    tracing: bool,
     entity_pool: impl Iterator<Item = (Data, Location)>,
 ) -> impl Iterator<Item = Hit<Data>>
 where
@@ -70,17 +139,26 @@ where
     )
     .entered();
 
-    trace!(
-        "starting cast at origin {}+{} = {}",
-        origin.tile,
-        origin.ticker,
-        origin.tile.as_vec3() + origin.ticker
-    );
-    trace!("vel: {} normalized: {}", ray_vel, ray_vel.normalize());
+    if tracing {
+        trace!(
+            "starting cast at origin {}+{} = {}",
+            origin.tile,
+            origin.ticker,
+            origin.tile.as_vec3() + origin.ticker
+        );
+    }
+    // a ray with no velocity, or too little to have a direction, can only hit its own tile
+    let direction = ray_vel.try_normalize();
+    let moving = direction.is_some();
+    let direction = direction.unwrap_or(Vec3::ZERO);
+
+    if tracing {
+        trace!("vel: {} normalized: {}", ray_vel, direction);
+    }
 
     let ray = bevy_math::Ray {
         origin: origin.tile.as_vec3() + origin.ticker,
-        direction: ray_vel.normalize(),
+        direction,
     };
 
     entity_pool.filter_map(move |(data, transform)| {
@@ -93,14 +171,19 @@ where
         let tile_translation_vec3 = tile_translation.as_vec3();
 
         if tile_translation == origin.tile {
-            return Some(Hit {
+            return include_origin.then_some(Hit {
                 offset: IVec3::ZERO,
                 distance: 0.,
+                normal: IVec3::ZERO,
                 translation: tile_translation,
                 data,
             });
         };
 
+        if !moving {
+            return None;
+        }
+
         // TODO: see if there's some way to get better perf here. Could do like bevy_translation.x /
         // ray.vel.x after translating so ray.origin is [0,0,0], Then use that as distance/scale
         // factor?
@@ -110,33 +193,231 @@ where
         let casted_to_distance =
             ray.origin + (ray.direction * expected_distance);
 
-        // account for epsilon to be safe
         // round distance because everything will be on grid
         //
         // FIXME: instead of rounding check if they're within Vec3::ONE of each other. As that
         // should be on same tile
-        let has_hit = Vec3::cmple(
-            (casted_to_distance.round() - tile_translation_vec3).abs(),
-            Vec3::splat(f32::EPSILON),
-        )
-        .all();
-
-        trace!(
-            "checking {tile_translation_vec3}; expected_distance: {expected_distance}; casted: {casted_to_distance}; ",
+        let has_hit = utils::approx_eq_vec3(
+            casted_to_distance.round(),
+            tile_translation_vec3,
+            utils::TOLERANCE,
         );
-        trace!("rounded distance: {}", casted_to_distance.round());
-        trace!("closeness: {}", (casted_to_distance.round() - tile_translation_vec3).abs());
-        trace!("{has_hit}");
+
+        if tracing {
+            trace!(
+                "checking {tile_translation_vec3}; expected_distance: {expected_distance}; casted: {casted_to_distance}; ",
+            );
+            trace!("rounded distance: {}", casted_to_distance.round());
+            trace!("closeness: {}", (casted_to_distance.round() - tile_translation_vec3).abs());
+            trace!("{has_hit}");
+        }
 
         has_hit.then_some(Hit {
             data,
             offset: tile_translation - origin.tile,
             translation: tile_translation,
             distance: expected_distance,
+            normal: entry_normal(ray.origin, ray.direction, tile_translation_vec3),
         })
     })
 }
 
+/// [`tile_cast`] along x and y only, eg. for top-down line of sight. Elevation is ignored, so
+/// anything above or below a tile on the ray is hit as well.
+///
+/// Both the ray and every candidate are flattened onto the origin's z level before casting. Each
+/// hit's `translation` and `offset` are still its real, unflattened ones, but its `distance` is
+/// only along x and y.
+#[inline]
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn tile_cast_2d<Data, Location>(
+    origin: Origin,
+    ray_vel: Vec3,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+) -> impl Iterator<Item = Hit<Data>>
+where
+    Location: GetTileLocation,
+{
+    let origin_tile = origin.tile;
+    let flatten = move |tile: IVec3| tile.truncate().extend(origin_tile.z);
+
+    tile_cast(
+        Origin {
+            tile: origin.tile,
+            ticker: origin.ticker.truncate().extend(0.),
+        },
+        ray_vel.truncate().extend(0.),
+        tile_stretch,
+        include_origin,
+        entity_pool.map(move |(data, location)| {
+            let tile = location.location(tile_stretch);
+            ((data, tile), flatten(tile))
+        }),
+    )
+    .map(move |hit| {
+        let (data, tile) = hit.data;
+        Hit {
+            translation: tile,
+            offset: tile - origin_tile,
+            distance: hit.distance,
+            normal: hit.normal,
+            data,
+        }
+    })
+}
+
+/// The closest hit of a [`tile_cast`], if anything was hit. See [`Hit::cmp_distance`] for how ties
+/// are broken.
+#[inline]
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn tile_cast_first<Data, Location>(
+    origin: Origin,
+    ray_vel: Vec3,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+) -> Option<Hit<Data>>
+where
+    Location: GetTileLocation,
+{
+    tile_cast(origin, ray_vel, tile_stretch, include_origin, entity_pool).min_by(Hit::cmp_distance)
+}
+
+/// [`tile_cast`], but skipping any hit more than `max_distance` away from the ray's origin, ie.
+/// `origin.tile + origin.ticker`. With a `max_distance` of `None` this is the same as [`tile_cast`].
+#[inline]
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn tile_cast_bounded<Data, Location>(
+    origin: Origin,
+    ray_vel: Vec3,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    max_distance: Option<f32>,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+) -> impl Iterator<Item = Hit<Data>>
+where
+    Location: GetTileLocation,
+{
+    tile_cast(origin, ray_vel, tile_stretch, include_origin, entity_pool)
+        .filter(move |hit| !max_distance.is_some_and(|max| hit.distance > max))
+}
+
+/// [`tile_cast`], but stopping at the first hit that `blocks` the ray, eg. for line of sight or
+/// bullets. Hits are returned closest first.
+///
+/// The blocking hit itself is returned, along with anything else at the same distance, but nothing
+/// farther. For colliders, `blocks` will usually be
+/// [`Constraints::violates_solidity`](crate::collision::Constraints::violates_solidity) with the
+/// ray's velocity.
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn occluded_tile_cast<Data, Location>(
+    origin: Origin,
+    ray_vel: Vec3,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+    blocks: impl Fn(&Data) -> bool,
+) -> Vec<Hit<Data>>
+where
+    Location: GetTileLocation,
+{
+    let mut hits: Vec<_> =
+        tile_cast(origin, ray_vel, tile_stretch, include_origin, entity_pool).collect();
+    hits.sort_by(Hit::cmp_distance);
+
+    if let Some(blocked_at) = hits
+        .iter()
+        .find(|hit| blocks(&hit.data))
+        .map(|hit| hit.distance)
+    {
+        hits.retain(|hit| hit.distance <= blocked_at);
+    }
+
+    hits
+}
+
+/// Collect the hits from a tile cast into a map keyed by `key`, for quick lookups of whether or not
+/// a specific thing was hit.
+///
+/// If two hits share a key, only the closest will be kept.
+pub fn collect_by<Data, Key>(
+    hits: impl Iterator<Item = Hit<Data>>,
+    key: impl Fn(&Data) -> Key,
+) -> HashMap<Key, Hit<Data>>
+where
+    Key: Eq + std::hash::Hash,
+{
+    let mut map: HashMap<Key, Hit<Data>> = HashMap::new();
+
+    for hit in hits {
+        let key = key(&hit.data);
+
+        match map.get(&key) {
+            Some(existing) if existing.distance <= hit.distance => {}
+            _ => {
+                map.insert(key, hit);
+            }
+        }
+    }
+
+    map
+}
+
+/// Several [`tile_cast`]s fanned out across the xy plane, eg. for vision cones or area attacks.
+/// Returns every hit data once, at its closest hit, closest first.
+///
+/// `ray_count` rays are spread evenly from `half_angle` radians clockwise of `center_dir` to
+/// `half_angle` anticlockwise of it, so both edges of the cone are cast. A single ray is cast along
+/// `center_dir`. Like any ray, they spread apart with distance, so far away tiles between two rays
+/// may be missed if `ray_count` is too low for the range you care about.
+#[must_use = "Tile casting is a relatively expensive operation that shouldn't change state. You should not use it if you don't need the result."]
+pub fn cone_cast<Data, Location>(
+    origin: Origin,
+    center_dir: Vec3,
+    half_angle: f32,
+    ray_count: u16,
+    tile_stretch: TileStretch,
+    include_origin: bool,
+    entity_pool: impl Iterator<Item = (Data, Location)>,
+) -> Vec<Hit<Data>>
+where
+    Data: Clone + Eq + std::hash::Hash,
+    Location: GetTileLocation,
+{
+    // only find each location once, rather than once per ray
+    let entity_pool: Vec<_> = entity_pool
+        .map(|(data, location)| (data, location.location(tile_stretch)))
+        .collect();
+
+    let (first_angle, angle_between_rays) = if ray_count > 1 {
+        (-half_angle, half_angle * 2. / f32::from(ray_count - 1))
+    } else {
+        (0., 0.)
+    };
+
+    let hits = (0..ray_count).flat_map(|ray| {
+        let angle = first_angle + angle_between_rays * f32::from(ray);
+
+        tile_cast(
+            Origin {
+                tile: origin.tile,
+                ticker: origin.ticker,
+            },
+            Quat::from_rotation_z(angle) * center_dir,
+            tile_stretch,
+            include_origin,
+            entity_pool.iter().map(|(data, tile)| (data.clone(), *tile)),
+        )
+    });
+
+    let mut hits: Vec<_> = collect_by(hits, Clone::clone).into_values().collect();
+    hits.sort_by(Hit::cmp_distance);
+
+    hits
+}
+
 #[cfg(feature = "developer-tools")]
 pub(super) mod console {
     use bevy_core::Name;
@@ -144,14 +425,14 @@ pub(super) mod console {
     use bevy_math::prelude::*;
     use bevy_transform::prelude::*;
     use pirate_sim_console::{self as console, Output, PrintStringCommand};
-    use pirate_sim_core::tile_grid::TileStretch;
-    use std::{collections::VecDeque, error::Error};
+    use pirate_sim_core::tile_grid::{GetTileLocation, TileStretch};
+    use std::{collections::VecDeque, error::Error, fmt::Write};
 
     #[allow(clippy::module_name_repetitions)]
     pub fn raycast_console(input: VecDeque<console::Token>, commands: &mut Commands) {
-        // raycast start_x start_y start_z dir_x dir_y dir_z
+        // raycast start_x start_y start_z dir_x dir_y dir_z [max_distance]
 
-        if input.len() == 6 {
+        if input.len() == 6 || input.len() == 7 {
             // TODO: switch this to using try blocks once out of nightly
             let vectors_result = || -> Result<_, Box<dyn Error>> {
                 let start_x: i32 = input[0].string.parse()?;
@@ -160,10 +441,13 @@ pub(super) mod console {
                 let dir_x: f32 = input[3].string.parse()?;
                 let dir_y: f32 = input[4].string.parse()?;
                 let dir_z: f32 = input[5].string.parse()?;
+                let max_distance: Option<f32> =
+                    input.get(6).map(|t| t.string.parse()).transpose()?;
 
                 Ok((
                     IVec3::new(start_x, start_y, start_z),
                     Vec3::new(dir_x, dir_y, dir_z),
+                    max_distance,
                 ))
             }();
 
@@ -171,6 +455,8 @@ pub(super) mod console {
                 Ok(vectors) => commands.add(RaycastCommand {
                     start: vectors.0,
                     direction: vectors.1,
+                    ignore: None,
+                    max_distance: vectors.2,
                 }),
                 Err(e) => commands.add(PrintStringCommand(format!(
                     "Invalid arguments: error `{e}`",
@@ -178,15 +464,82 @@ pub(super) mod console {
             };
         } else {
             commands.add(PrintStringCommand(format!(
-                "Incorrect length: expected 6 arguments but was given {}",
+                "Incorrect length: expected 6 or 7 arguments but was given {}",
                 input.len()
             )));
         }
     }
 
-    struct RaycastCommand {
-        start: IVec3,
-        direction: Vec3,
+    pub fn raycast_from_console(input: VecDeque<console::Token>, commands: &mut Commands) {
+        // raycast_from name|@selected dir_x dir_y dir_z
+
+        if input.len() == 4 {
+            let direction_result = || -> Result<_, Box<dyn Error>> {
+                let dir_x: f32 = input[1].string.parse()?;
+                let dir_y: f32 = input[2].string.parse()?;
+                let dir_z: f32 = input[3].string.parse()?;
+
+                Ok(Vec3::new(dir_x, dir_y, dir_z))
+            }();
+
+            match direction_result {
+                Ok(direction) => commands.add(RaycastFromCommand {
+                    name: input[0].string.clone(),
+                    direction,
+                }),
+                Err(e) => commands.add(PrintStringCommand(format!(
+                    "Invalid arguments: error `{e}`",
+                ))),
+            }
+        } else {
+            commands.add(PrintStringCommand(format!(
+                "Incorrect length: expected 4 arguments but was given {}",
+                input.len()
+            )));
+        }
+    }
+
+    /// Print every entity along a ray, closest first, with its tile and distance from the start
+    pub(crate) struct RaycastCommand {
+        pub start: IVec3,
+        pub direction: Vec3,
+        /// skipped when printing hits, eg. the entity being cast from
+        pub ignore: Option<Entity>,
+        /// hits farther than this are skipped
+        pub max_distance: Option<f32>,
+    }
+
+    /// A [`RaycastCommand`] starting from a named entity's tile
+    pub(crate) struct RaycastFromCommand {
+        pub name: String,
+        pub direction: Vec3,
+    }
+
+    impl Command for RaycastFromCommand {
+        fn apply(self, world: &mut World) {
+            let tile_stretch = *world
+                .get_resource::<TileStretch>()
+                .expect("No tile stretch initialized??");
+
+            let start = console::find_target(world, &self.name).and_then(|entity| {
+                world
+                    .get::<GlobalTransform>(entity)
+                    .map(|transform| (entity, transform.location(tile_stretch)))
+            });
+
+            if let Some((entity, start)) = start {
+                RaycastCommand {
+                    start,
+                    direction: self.direction,
+                    ignore: Some(entity),
+                    max_distance: None,
+                }
+                .apply(world);
+            } else {
+                world.send_event(Output::String("Could not find entity".into()));
+                world.send_event(Output::End);
+            }
+        }
     }
 
     impl Command for RaycastCommand {
@@ -198,33 +551,34 @@ pub(super) mod console {
                 .expect("No tile stretch initialized??");
             let mut output = String::new();
 
-            let entities = super::tile_cast(
+            let entities = super::tile_cast_bounded(
                 super::Origin {
                     tile: self.start,
                     ticker: Vec3::ZERO,
                 },
                 self.direction,
                 *tile_stretch,
+                true,
+                self.max_distance,
                 entity_query.iter(world),
             );
 
-            for entity in entities.collect::<Box<[_]>>().iter() {
+            // sorted so that it's clear which order collisions would resolve in
+            let mut hits: Vec<_> = entities.collect();
+            hits.sort_by(super::Hit::cmp_distance);
+
+            for hit in hits.into_iter().filter(|hit| Some(hit.data) != self.ignore) {
                 // log name or whatever
                 let name = name_query
-                    .get(world, entity.data)
+                    .get(world, hit.data)
                     .map_or_else(|_| "UnNamed Entity", |n| n.as_str());
 
-                let translation = entity_query
-                    .get(world, entity.data)
-                    .expect("Entity found in raycast but has no translation. This is not possible")
-                    .1
-                    .translation();
-
-                output.push_str("Entity found in raycast:");
-                output.push_str(name);
-                output.push(':');
-                output.push_str(&translation.to_string());
-                output.push('\n');
+                writeln!(
+                    output,
+                    "Entity found in raycast:{name}:tile {}:distance {:.2}",
+                    hit.translation, hit.distance
+                )
+                .expect("writing to a String can't fail");
             }
 
             if output.is_empty() {
@@ -1,5 +1,7 @@
 //! Velocity calculations
 
+use std::marker::PhantomData;
+
 use bevy_app::{App, PostUpdate, Update};
 use bevy_core::Name;
 use bevy_derive::{Deref, DerefMut};
@@ -8,12 +10,17 @@ use bevy_hierarchy::{Children, Parent};
 use bevy_log::trace;
 use bevy_math::prelude::*;
 use bevy_reflect::prelude::*;
+use bevy_time::Time;
 use bevy_transform::prelude::*;
 
-use crate::{tile_cast, Collider};
+use crate::{
+    collision::{floor_below, CollisionLayers, CollisionMap, Passthrough},
+    tile_cast, Collider,
+};
 
 use pirate_sim_core::{
     system_sets::PhysicsSet,
+    tile_grid::{GetTileLocation, TileStretch},
     utils::{self, get_or_zero},
 };
 
@@ -40,6 +47,10 @@ pub(super) struct RelativeVelocity(pub Vec3);
 ///
 /// This is currently only guaranteed to be accurate between [`PhysicsSet::Velocity`] and
 /// [`PhysicsSet::Collision`]
+///
+/// [`TotalVelocity`] is owned by the physics engine during [`PhysicsSet::Velocity`], where it is
+/// zeroed and then propagated. Nothing else may write to it in between, which is checked in debug
+/// builds. In release builds any such write is overwritten.
 #[derive(Debug, Component, Clone, Default, Deref, DerefMut, Reflect)]
 pub(super) struct TotalVelocity(pub Vec3);
 
@@ -64,10 +75,58 @@ impl From<Vec3> for LastTotal {
 #[derive(Debug, Clone, Component, Default, Deref, DerefMut, Reflect)]
 pub struct Mantained(pub Vec3);
 
+/// Slow down an entity's horizontal [`Mantained`] velocity while it is standing on a floor, by this
+/// many tiles per second every second, until it stops.
+///
+/// Standing on a floor is the same as for [`StandingOn`](crate::collision::StandingOn), a collider
+/// solid on its positive z plane directly below.
+#[derive(Debug, Clone, Copy, Component, Default, PartialEq, Deref, DerefMut, Reflect)]
+pub struct Friction(pub f32);
+
+/// Ease an entity's velocity from its [`MovementGoal`](super::MovementGoal) towards the goal,
+/// instead of snapping to it, giving it some momentum. The goal is always reached exactly.
+///
+/// Other sources of velocity, like gravity, still apply immediately.
+#[derive(Debug, Clone, Component, Default, Reflect)]
+pub struct Acceleration {
+    /// How quickly the velocity changes, in tiles per second per second
+    pub rate: f32,
+    eased_goal: Vec3,
+}
+
+impl Acceleration {
+    #[must_use]
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            eased_goal: Vec3::ZERO,
+        }
+    }
+
+    /// How much of the goal is currently being applied
+    #[must_use]
+    pub fn eased_goal(&self) -> Vec3 {
+        self.eased_goal
+    }
+
+    /// Move the eased goal up to `max_change` closer to `goal`
+    fn ease_towards(&mut self, goal: Vec3, max_change: f32) -> Vec3 {
+        let difference = goal - self.eased_goal;
+
+        self.eased_goal = if difference.length() <= max_change {
+            goal
+        } else {
+            self.eased_goal + difference.normalize_or_zero() * max_change
+        };
+
+        self.eased_goal
+    }
+}
+
 #[derive(Clone, Component, Default, Reflect)]
 pub struct FromGround(Vec3);
 
-fn zero_total_vel(mut total_vel_q: Query<&mut TotalVelocity>) {
+pub(crate) fn zero_total_vel(mut total_vel_q: Query<&mut TotalVelocity>) {
     total_vel_q.iter_mut().for_each(|mut t| {
         *t = TotalVelocity(Vec3::ZERO);
     });
@@ -147,31 +206,50 @@ fn propagate_from_ground(
 /// This does not move any components, nor update their ticker
 ///
 /// This should wait until movement finalization to multiply by delta time.
-fn calculate_relative_velocity(
+pub(crate) fn calculate_relative_velocity(
     mut phsyics_components: Query<(
         &mut RelativeVelocity,
         Option<&super::MovementGoal>,
         Option<&super::Weight>,
         Option<&Mantained>,
         Option<&FromGround>,
+        Option<&mut Acceleration>,
+        Option<&mut crate::collision::Restitution>,
     )>,
+    gravity: Res<super::Gravity>,
+    gravity_enabled: Res<super::GravityEnabled>,
+    time: Res<Time>,
+    timestep: Res<crate::PhysicsTimestep>,
 ) {
+    let delta_time = timestep.delta_seconds(&time);
+
     for component in phsyics_components.iter_mut() {
         let mut new_relative_velocity = Vec3::splat(0.);
 
-        let (mut relative_velocity, movement_goal, weight, mantained, from_ground) = component;
+        let (
+            mut relative_velocity,
+            movement_goal,
+            weight,
+            mantained,
+            from_ground,
+            acceleration,
+            restitution,
+        ) = component;
 
         // it is up to the controller to ensure that the movement goal is reasonable
-        if let Some(movement_goal) = movement_goal {
-            new_relative_velocity += movement_goal.0;
+        let goal = movement_goal.map_or(Vec3::ZERO, |g| g.0);
+        if let Some(mut acceleration) = acceleration {
+            let max_change = acceleration.rate * delta_time;
+            new_relative_velocity += acceleration.ease_towards(goal, max_change);
+        } else {
+            new_relative_velocity += goal;
         }
 
         // maybe gravity should be part of maintained velocity
-        if let Some(weight) = weight {
-            if **weight != 0. {
-                new_relative_velocity.z -= super::GRAVITY;
-            }
-        }
+        let applied_gravity = weight
+            .filter(|weight| **gravity_enabled && ***weight != 0.)
+            .map_or(Vec3::ZERO, |_| gravity.0);
+        new_relative_velocity += applied_gravity;
 
         if let Some(mantained) = mantained {
             new_relative_velocity += mantained.0;
@@ -181,10 +259,96 @@ fn calculate_relative_velocity(
             new_relative_velocity += from_ground.0;
         }
 
+        // see Restitution
+        if let Some(mut restitution) = restitution {
+            new_relative_velocity += restitution.rebound();
+            restitution.slow_rebound(applied_gravity.z.abs() * delta_time);
+        }
+
         relative_velocity.0 = new_relative_velocity;
     }
 }
 
+/// A source of velocity defined outside of the physics engine, eg. conveyor belts or magnets.
+///
+/// Every entity with a contributor has [`VelocityContributor::velocity`] added to its
+/// [`RelativeVelocity`] each frame, after the engine's own sources. Contributors must be registered
+/// with a [`ContributorPlugin`] to take effect.
+pub trait VelocityContributor: Component {
+    /// The velocity this contributes, in tiles per second
+    fn velocity(&self) -> Vec3;
+}
+
+fn add_contributions<C: VelocityContributor>(
+    mut contributor_q: Query<(&C, &mut RelativeVelocity)>,
+) {
+    for (contributor, mut relative_velocity) in &mut contributor_q {
+        relative_velocity.0 += contributor.velocity();
+    }
+}
+
+/// Registers a [`VelocityContributor`] with the physics engine
+///
+/// ```ignore
+/// app.add_plugins(ContributorPlugin::<ConveyorBelt>::default());
+/// ```
+pub struct ContributorPlugin<C: VelocityContributor>(PhantomData<C>);
+
+impl<C: VelocityContributor> Default for ContributorPlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: VelocityContributor> bevy_app::Plugin for ContributorPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            add_contributions::<C>
+                .in_set(PhysicsSet::Velocity)
+                .after(calculate_relative_velocity)
+                .before(crate::movement::apply_leashes)
+                .before(propagate_velocities),
+        );
+    }
+}
+
+/// Apply [`Friction`] to grounded entities, before their [`Mantained`] velocity is used
+fn apply_friction(
+    mut friction_q: Query<(Entity, &Friction, &mut Mantained, &GlobalTransform)>,
+    tile_stretch: Res<TileStretch>,
+    collision_map: Res<CollisionMap>,
+    layers_q: Query<&CollisionLayers>,
+    passthrough_q: Query<&Passthrough>,
+    time: Res<Time>,
+    timestep: Res<crate::PhysicsTimestep>,
+) {
+    let delta_time = timestep.delta_seconds(&time);
+
+    for (entity, friction, mut mantained, global_transform) in &mut friction_q {
+        let horizontal = mantained.0.truncate();
+        if horizontal == Vec2::ZERO {
+            continue;
+        }
+
+        // FloorSnap keeps anything resting on a floor from ever colliding with it, so this goes by
+        // what's below rather than the last collision
+        let grounded = floor_below(
+            &collision_map,
+            entity,
+            global_transform.location(*tile_stretch),
+            &layers_q,
+            &passthrough_q,
+        )
+        .is_some();
+
+        if grounded {
+            let speed = (horizontal.length() - friction.0 * delta_time).max(0.);
+            mantained.0 = (horizontal.normalize() * speed).extend(mantained.0.z);
+        }
+    }
+}
+
 /// This function decays any persistent velocities.
 ///
 /// It needs a rework, and is currently not used
@@ -226,7 +390,7 @@ fn decay_persistent_velocity(mut velocity: Query<&mut Mantained>) {
 /// or <https://github.com/bevyengine/bevy/LICENSE-MIT>
 ///
 /// TODO: Reintroduce change detection checking
-fn propagate_velocities(
+pub(crate) fn propagate_velocities(
     mut root_query: Query<
         (
             Entity,
@@ -242,20 +406,28 @@ fn propagate_velocities(
     >,
     parent_query: Query<(Entity, Ref<Parent>)>,
     name_query: Query<&Name>,
+    log_config: Res<crate::PhysicsLogConfig>,
 ) {
-    trace!("starting velocity propagataion");
+    let tracing = log_config.trace_velocity;
+
+    if tracing {
+        trace!("starting velocity propagataion");
+    }
 
     root_query
         .par_iter_mut()
         .for_each_mut(|(entity, children, relative, mut total)| {
-            trace!(
-                "propogating root {}",
-                name_query
-                    .get(entity)
-                    .map_or_else(|_| "UnnamedEntity".into(), ToString::to_string)
-            );
+            if tracing {
+                trace!(
+                    "propogating root {}",
+                    name_query
+                        .get(entity)
+                        .map_or_else(|_| "UnnamedEntity".into(), ToString::to_string)
+                );
+            }
 
-            total.0 += relative.0;
+            debug_assert_eq!(total.0, Vec3::ZERO, "{TOTAL_WRITTEN_MESSAGE}");
+            total.0 = relative.0;
 
             let Some(children) = children else {return};
 
@@ -268,6 +440,9 @@ fn propagate_velocities(
         });
 }
 
+const TOTAL_WRITTEN_MESSAGE: &str =
+    "TotalVelocity was written to between being zeroed and propagated";
+
 /// This is lifted from the bevy source code, which is dual-licensed under the Apache 2.0, and MIT
 /// license. see <https://github.com/bevyengine/bevy/LICENSE-APACHE> or <./../credits/> for more details.
 unsafe fn propagate_recursive(
@@ -311,7 +486,8 @@ unsafe fn propagate_recursive(
                 return;
             };
 
-        total.0 += parent_total.0 + relative.0;
+        debug_assert_eq!(total.0, Vec3::ZERO, "{TOTAL_WRITTEN_MESSAGE}");
+        total.0 = parent_total.0 + relative.0;
         (parent_total, children)
     };
 
@@ -338,6 +514,99 @@ unsafe fn propagate_recursive(
     }
 }
 
+#[cfg(feature = "developer-tools")]
+pub(super) mod console {
+    use bevy_ecs::{prelude::*, system::Command};
+    use pirate_sim_console::{self as console, Output, PrintStringCommand};
+    use std::collections::VecDeque;
+
+    use crate::{GravityEnabled, Weight};
+
+    pub fn gravity_console(input: VecDeque<console::Token>, commands: &mut Commands) {
+        // gravity on|off
+
+        let enabled = match input.front().map(|t| t.string.as_str()) {
+            Some("on") if input.len() == 1 => true,
+            Some("off") if input.len() == 1 => false,
+            _ => {
+                commands.add(PrintStringCommand("Usage: gravity on|off".into()));
+                return;
+            }
+        };
+
+        commands.add(GravityCommand { enabled });
+    }
+
+    pub(crate) struct GravityCommand {
+        pub enabled: bool,
+    }
+
+    impl Command for GravityCommand {
+        fn apply(self, world: &mut World) {
+            world.insert_resource(GravityEnabled(self.enabled));
+
+            let state = if self.enabled { "enabled" } else { "disabled" };
+            world.send_event(Output::String(format!("Gravity {state}")));
+            world.send_event(Output::End);
+        }
+    }
+
+    pub fn weight_console(input: VecDeque<console::Token>, commands: &mut Commands) {
+        // weight name|@selected [value]
+
+        let weight = match input.len() {
+            1 => None,
+            2 => match input[1].string.parse::<f32>() {
+                Ok(weight) if weight.is_finite() => Some(weight),
+                _ => {
+                    commands.add(PrintStringCommand(format!(
+                        "Invalid weight `{}`: expected a number",
+                        input[1].string
+                    )));
+                    return;
+                }
+            },
+            _ => {
+                commands.add(PrintStringCommand(
+                    "Usage: weight name|@selected [value]".into(),
+                ));
+                return;
+            }
+        };
+
+        commands.add(WeightCommand {
+            name: input[0].string.clone(),
+            weight,
+        });
+    }
+
+    /// Print an entity's [`Weight`], or set it to `weight`, adding one if it has none
+    pub(crate) struct WeightCommand {
+        pub name: String,
+        pub weight: Option<f32>,
+    }
+
+    impl Command for WeightCommand {
+        fn apply(self, world: &mut World) {
+            let output = match (console::find_target(world, &self.name), self.weight) {
+                (None, _) => "Could not find entity".into(),
+                (Some(entity), None) => match world.get::<Weight>(entity) {
+                    Some(weight) => format!("{} weighs {}", self.name, weight.0),
+                    None => format!("{} has no weight", self.name),
+                },
+                (Some(entity), Some(weight)) => {
+                    world.entity_mut(entity).insert(Weight(weight));
+
+                    format!("Set the weight of {} to {weight}", self.name)
+                }
+            };
+
+            world.send_event(Output::String(output));
+            world.send_event(Output::End);
+        }
+    }
+}
+
 /// You probably want [`super::movement::MovementBundle`]
 ///
 /// This bundle allows an entity to be acted on by all systems in the velocity module/plugin
@@ -353,21 +622,30 @@ pub struct Plugin;
 
 impl bevy_app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, zero_total_vel.before(calculate_relative_velocity))
-            .add_systems(
-                Update,
-                (calculate_relative_velocity, propagate_velocities)
-                    .chain()
-                    .in_set(PhysicsSet::Velocity),
+        app.add_systems(
+            Update,
+            (
+                zero_total_vel,
+                calculate_relative_velocity,
+                propagate_velocities,
             )
-            .add_systems(
-                PostUpdate,
-                (
-                    propagate_from_ground,
-                    update_last::<TotalVelocity, LastTotal>,
-                    update_last::<RelativeVelocity, LastRelative>,
-                ),
-            );
+                .chain()
+                .in_set(PhysicsSet::Velocity),
+        )
+        .add_systems(
+            Update,
+            apply_friction
+                .in_set(PhysicsSet::Velocity)
+                .before(calculate_relative_velocity),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                propagate_from_ground,
+                update_last::<TotalVelocity, LastTotal>,
+                update_last::<RelativeVelocity, LastRelative>,
+            ),
+        );
         // don't put in
         // Velocity as it can actually run during input
     }
@@ -1,10 +1,19 @@
 use std::{collections::VecDeque, str::FromStr};
 
-use pirate_sim_core::tile_grid::TileStretch;
+use pirate_sim_core::{
+    spawn_budget::{SpawnBudget, SpawnTracked},
+    tile_grid::{GetTileLocation, TileStretch},
+};
+use pirate_sim_physics::{collision::Constraints, Collider, PhysicsComponentBase};
 
 use bevy::app::AppExit;
+use bevy::ecs::system::Command;
 use bevy::prelude::*;
-use pirate_sim_console::{registration::RegisterConsoleCommand, Output, PrintStringCommand, Token};
+use pirate_sim_console::{
+    apply_to_target, registration::RegisterConsoleCommand, Output, PrintStringCommand, Token,
+};
+
+use pirate_sim_game::tile_objects::{SpriteSheetHandle, TileObject};
 
 fn echo_command(input: VecDeque<Token>, commands: &mut Commands) {
     commands.add(PrintStringCommand(
@@ -20,6 +29,8 @@ fn exit_command(_input: VecDeque<Token>, commands: &mut Commands) {
 }
 
 fn move_command(mut input: VecDeque<Token>, commands: &mut Commands) {
+    // move name|@selected x y z
+
     if input.len() != 4 {
         commands.add(PrintStringCommand(format!(
             "Wrong amount of inputs. Expected 4, got {}",
@@ -42,29 +53,18 @@ fn move_command(mut input: VecDeque<Token>, commands: &mut Commands) {
 
     match parsed {
         Ok(new_translation) => commands.add(move |world: &mut World| {
-            let mut name_query = world.query::<(Entity, &Name)>();
-            let mut location_query = world.query::<&mut Transform>();
-            let output: String;
-
-            let to_move = name_query
-                .iter(world)
-                .find_map(|e| (e.1.as_str() == name).then_some(e.0));
-
-            match to_move {
-                Some(new_entity) => {
-                    let tile_stretch = *world.resource::<TileStretch>();
-
-                    let transform = location_query.get_mut(world, new_entity);
-
-                    if let Ok(mut transform) = transform {
-                        *transform =
-                            transform.with_translation(tile_stretch.get_bevy(new_translation));
-                    }
+            let tile_stretch = *world.resource::<TileStretch>();
 
-                    output = "Moved an entity".into();
+            let moved = apply_to_target(world, &name, |entity, world| {
+                if let Some(mut transform) = world.get_mut::<Transform>(entity) {
+                    transform.translation = tile_stretch.get_bevy(new_translation);
                 }
-                None => output = "Could not find entity".into(),
-            }
+            });
+
+            let output = match moved {
+                0 => "Could not find entity".into(),
+                moved => format!("Moved {moved} entities"),
+            };
 
             world.send_event(Output::String(output));
             world.send_event(Output::End);
@@ -73,13 +73,299 @@ fn move_command(mut input: VecDeque<Token>, commands: &mut Commands) {
     }
 }
 
+fn spawngrid_command(mut input: VecDeque<Token>, commands: &mut Commands) {
+    // spawngrid <w> <h> <kind>
+
+    if input.len() != 3 {
+        commands.add(PrintStringCommand(format!(
+            "Wrong amount of inputs. Expected 3, got {}",
+            input.len()
+        )));
+        return;
+    }
+
+    #[allow(clippy::unwrap_used)]
+    let parsed = || -> Result<(u16, u16), <u16 as FromStr>::Err> {
+        let width = input.pop_front().unwrap().string.parse::<u16>()?;
+        let height = input.pop_front().unwrap().string.parse::<u16>()?;
+
+        Ok((width, height))
+    }();
+
+    let (width, height) = match parsed {
+        Ok(dimensions) => dimensions,
+        Err(e) => {
+            commands.add(PrintStringCommand(format!("Parsing error `{e}`")));
+            return;
+        }
+    };
+
+    #[allow(clippy::unwrap_used)]
+    let kind = input.pop_front().unwrap().string;
+
+    let Some(constraints) = Constraints::from_name(&kind) else {
+        commands.add(PrintStringCommand(format!(
+            "Unknown kind `{kind}`. Expected one of wall, floor, one_way, entity, or sensor"
+        )));
+        return;
+    };
+
+    // walls look like walls, and everything else gets the floor sprite
+    let tile_object = if kind == "wall" {
+        TileObject::new(202, 203, 204)
+    } else {
+        TileObject::new(5, 6, 7)
+    };
+
+    commands.add(move |world: &mut World| {
+        let tile_stretch = *world.resource::<TileStretch>();
+        let spritesheet_handle = world.resource::<SpriteSheetHandle>().0.clone();
+
+        let blocked = |world: &World| {
+            world
+                .get_resource::<SpawnBudget>()
+                .map_or(0, SpawnBudget::blocked_this_frame)
+        };
+        let blocked_before = blocked(world);
+
+        let locations = (0..height)
+            .flat_map(move |y| (0..width).map(move |x| IVec3::new(i32::from(x), i32::from(y), 0)));
+
+        for location in locations {
+            SpawnTracked((
+                Collider::new(constraints),
+                tile_object,
+                Name::new(format!("Grid {kind}")),
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite::new(tile_object.main_layer_index),
+                    texture_atlas: spritesheet_handle.clone(),
+                    transform: Transform::from_translation(tile_stretch.get_bevy(location)),
+                    ..default()
+                },
+            ))
+            .apply(world);
+        }
+
+        // anything over the spawn budget was dropped
+        let spawned = usize::from(width) * usize::from(height) - (blocked(world) - blocked_before);
+        world.send_event(Output::String(format!("Spawned {spawned} colliders")));
+        world.send_event(Output::End);
+    });
+}
+
+/// The highest tile in `column` with a collider that can be stood on, ie. is solid along its
+/// positive z plane
+pub fn topmost_floor(world: &mut World, column: IVec2) -> Option<IVec3> {
+    let tile_stretch = *world.resource::<TileStretch>();
+
+    world
+        .query::<(&Collider, &GlobalTransform)>()
+        .iter(world)
+        .filter(|(collider, _)| collider.constraints.pos_solid_planes.z)
+        .map(|(_, transform)| transform.location(tile_stretch))
+        .filter(|tile| tile.truncate() == column)
+        .max_by_key(|tile| tile.z)
+}
+
+fn spawn_on_floor_command(mut input: VecDeque<Token>, commands: &mut Commands) {
+    // spawn_on_floor <x> <y>
+
+    if input.len() != 2 {
+        commands.add(PrintStringCommand(format!(
+            "Wrong amount of inputs. Expected 2, got {}",
+            input.len()
+        )));
+        return;
+    }
+
+    #[allow(clippy::unwrap_used)]
+    let parsed = || -> Result<IVec2, <i32 as FromStr>::Err> {
+        let x = input.pop_front().unwrap().string.parse::<i32>()?;
+        let y = input.pop_front().unwrap().string.parse::<i32>()?;
+
+        Ok(IVec2::new(x, y))
+    }();
+
+    let column = match parsed {
+        Ok(column) => column,
+        Err(e) => {
+            commands.add(PrintStringCommand(format!("Parsing error `{e}`")));
+            return;
+        }
+    };
+
+    commands.add(move |world: &mut World| {
+        let output = if let Some(floor) = topmost_floor(world, column) {
+            let tile_stretch = *world.resource::<TileStretch>();
+            let spritesheet_handle = world.resource::<SpriteSheetHandle>().0.clone();
+            let location = floor + IVec3::Z;
+
+            world.spawn((
+                Name::new("Spawned"),
+                PhysicsComponentBase::default(),
+                Collider::entity(),
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite::new(2),
+                    texture_atlas: spritesheet_handle,
+                    transform: Transform::from_translation(tile_stretch.get_bevy(location)),
+                    ..default()
+                },
+            ));
+
+            format!("Spawned at {location}")
+        } else {
+            format!("No floor at {column}")
+        };
+
+        world.send_event(Output::String(output));
+        world.send_event(Output::End);
+    });
+}
+
 pub(super) fn setup_basic_commands(mut commands: Commands) {
     // register each command in this array
     for to_register in [
         RegisterConsoleCommand::new("echo".into(), echo_command),
         RegisterConsoleCommand::new("exit".into(), exit_command),
         RegisterConsoleCommand::new("move".into(), move_command),
+        RegisterConsoleCommand::new("spawngrid".into(), spawngrid_command),
+        RegisterConsoleCommand::new("spawn_on_floor".into(), spawn_on_floor_command),
+        RegisterConsoleCommand::new(
+            "spawnship".into(),
+            crate::ships::creation::console::spawnship_console,
+        ),
     ] {
         commands.add(to_register);
     }
 }
+
+#[cfg(test)]
+#[test]
+fn spawngrid_spawns_colliders() {
+    use bevy::ecs::system::CommandQueue;
+    use pirate_sim_console::parse;
+
+    let mut world = World::new();
+
+    world.insert_resource(TileStretch::new(32, 32));
+    world.insert_resource(SpriteSheetHandle(Handle::default()));
+    world.init_resource::<Events<Output>>();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    spawngrid_command(parse("3 4 wall").expect("arguments parse"), &mut commands);
+    queue.apply(&mut world);
+
+    let mut collider_q = world.query::<(&Collider, &Transform)>();
+    let colliders: Vec<_> = collider_q.iter(&world).collect();
+
+    assert_eq!(colliders.len(), 12);
+    assert!(colliders
+        .iter()
+        .any(|(_, t)| t.translation == Vec3::new(64., 96., 0.)));
+
+    // an unknown kind shouldn't spawn anything
+    let mut commands = Commands::new(&mut queue, &world);
+    spawngrid_command(parse("3 4 boat").expect("arguments parse"), &mut commands);
+    queue.apply(&mut world);
+
+    assert_eq!(world.query::<&Collider>().iter(&world).count(), 12);
+
+    // only as many as the spawn budget allows, which is what's reported
+    world.insert_resource(SpawnBudget::new(5));
+    world.resource_mut::<Events<Output>>().clear();
+
+    let mut commands = Commands::new(&mut queue, &world);
+    spawngrid_command(parse("3 4 floor").expect("arguments parse"), &mut commands);
+    queue.apply(&mut world);
+
+    assert_eq!(world.query::<&Collider>().iter(&world).count(), 17);
+    assert!(world
+        .resource_mut::<Events<Output>>()
+        .drain()
+        .any(|o| matches!(o, Output::String(s) if s == "Spawned 5 colliders")));
+}
+
+#[cfg(test)]
+#[test]
+#[allow(clippy::unwrap_used)]
+fn move_accepts_selected() {
+    use bevy::ecs::system::CommandQueue;
+    use pirate_sim_console::{parse, SelectedEntity};
+
+    let mut world = World::new();
+
+    world.insert_resource(TileStretch::new(32, 32));
+    world.init_resource::<Events<Output>>();
+
+    let selected = world
+        .spawn((Name::new("Selected"), TransformBundle::default()))
+        .id();
+    let other = world
+        .spawn((Name::new("Other"), TransformBundle::default()))
+        .id();
+    world.insert_resource(SelectedEntity(Some(selected)));
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    move_command(
+        parse("@selected 1 1 0").expect("arguments parse"),
+        &mut commands,
+    );
+    queue.apply(&mut world);
+
+    assert_eq!(
+        world.get::<Transform>(selected).unwrap().translation,
+        Vec3::new(32., 32., 0.)
+    );
+    assert_eq!(
+        world.get::<Transform>(other).unwrap().translation,
+        Vec3::ZERO
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn spawn_on_floor_lands_above_topmost_floor() {
+    use bevy::ecs::system::CommandQueue;
+    use pirate_sim_console::parse;
+
+    let mut world = World::new();
+
+    world.insert_resource(TileStretch::new(32, 32));
+    world.insert_resource(SpriteSheetHandle(Handle::default()));
+    world.init_resource::<Events<Output>>();
+
+    // two floors stacked in one column, and a taller one in the column beside it
+    for (x, z) in [(32., 0.), (32., 2.), (64., 5.)] {
+        let translation = Vec3::new(x, 32., z);
+        world.spawn((
+            Collider::floor(),
+            Transform::from_translation(translation),
+            GlobalTransform::from_translation(translation),
+        ));
+    }
+
+    let spawn_at = |world: &mut World, x: &str, y: &str| {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        spawn_on_floor_command(
+            parse(&format!("{x} {y}")).expect("arguments parse"),
+            &mut commands,
+        );
+        queue.apply(world);
+    };
+
+    spawn_at(&mut world, "1", "1");
+
+    let spawned: Vec<_> = world
+        .query::<(&Name, &Transform)>()
+        .iter(&world)
+        .map(|(_, t)| t.translation)
+        .collect();
+    assert_eq!(spawned, vec![Vec3::new(32., 32., 3.)]);
+
+    // nothing to stand on
+    spawn_at(&mut world, "3", "1");
+    assert_eq!(world.query::<&Name>().iter(&world).count(), 1);
+}
@@ -0,0 +1,91 @@
+//! A developer readout showing the current [`MovementGoal`] of every named entity
+//!
+//! Each named entity with a movement goal gets a child text entity hovering above it, which is
+//! kept up to date as its goal changes. Useful when tuning AI.
+
+use bevy::prelude::*;
+
+use pirate_sim_core::tile_grid::TileStretch;
+use pirate_sim_physics::MovementGoal;
+
+/// Points from an entity to the text entity displaying its movement goal
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct GoalReadout(Entity);
+
+const READOUT_FONT_SIZE: f32 = 12.;
+
+fn readout_string(name: &Name, goal: &MovementGoal) -> String {
+    format!("{name}: {}", goal.0)
+}
+
+/// Spawn readouts for any new named goal-bearing entity, and update existing ones when their goal
+/// changes
+pub fn update_goal_readouts(
+    mut commands: Commands,
+    goal_q: Query<(Entity, &Name, Ref<MovementGoal>, Option<&GoalReadout>)>,
+    mut text_q: Query<&mut Text>,
+    tile_stretch: Res<TileStretch>,
+) {
+    for (entity, name, goal, readout) in &goal_q {
+        match readout.and_then(|r| text_q.get_mut(r.0).ok()) {
+            Some(mut text) => {
+                if goal.is_changed() {
+                    text.sections[0].value = readout_string(name, &goal);
+                }
+            }
+            None => {
+                // float the readout half a tile above the entity
+                let offset = Vec3::new(0., f32::from(tile_stretch.y) / 2., 0.1);
+
+                let text_entity = commands
+                    .spawn(Text2dBundle {
+                        text: Text::from_section(
+                            readout_string(name, &goal),
+                            TextStyle {
+                                font_size: READOUT_FONT_SIZE,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                        transform: Transform::from_translation(offset),
+                        ..default()
+                    })
+                    .set_parent(entity)
+                    .id();
+
+                commands.entity(entity).insert(GoalReadout(text_entity));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn goal_readout_smoke() {
+    let mut app = App::new();
+
+    app.insert_resource(TileStretch::new(32, 32))
+        .add_systems(Update, update_goal_readouts);
+
+    let entity = app
+        .world
+        .spawn((
+            Name::new("Goal haver"),
+            MovementGoal(Vec3::X),
+            TransformBundle::default(),
+        ))
+        .id();
+
+    app.update();
+
+    let readout = app
+        .world
+        .get::<GoalReadout>(entity)
+        .expect("readout should have been spawned");
+    let text = app
+        .world
+        .get::<Text>(readout.0)
+        .expect("readout should have text");
+
+    assert!(text.sections[0].value.starts_with("Goal haver"));
+}
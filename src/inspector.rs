@@ -0,0 +1,132 @@
+//! Showing and hiding the world inspector at runtime, with a key or the `inspector on|off` console
+//! command
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use pirate_sim_console::{registration::RegisterConsoleCommand, PrintStringCommand, Token};
+
+/// Whether the world inspector is shown, and which key flips it
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct InspectorToggle {
+    pub shown: bool,
+    pub key: KeyCode,
+}
+
+impl Default for InspectorToggle {
+    fn default() -> Self {
+        Self {
+            shown: true,
+            key: KeyCode::F1,
+        }
+    }
+}
+
+impl InspectorToggle {
+    pub fn toggle(&mut self) {
+        self.shown = !self.shown;
+    }
+}
+
+/// Run condition for anything that should only happen while the inspector is shown
+fn inspector_shown(toggle: Res<InspectorToggle>) -> bool {
+    toggle.shown
+}
+
+fn toggle_inspector_on_key(keys: Res<Input<KeyCode>>, mut toggle: ResMut<InspectorToggle>) {
+    if keys.just_pressed(toggle.key) {
+        toggle.toggle();
+    }
+}
+
+fn inspector_command(input: VecDeque<Token>, commands: &mut Commands) {
+    // inspector on|off
+
+    let shown = match input.iter().map(|t| t.string.as_str()).collect::<Vec<_>>()[..] {
+        ["on"] => true,
+        ["off"] => false,
+        _ => {
+            commands.add(PrintStringCommand("Expected `on` or `off`".into()));
+            return;
+        }
+    };
+
+    commands.add(move |world: &mut World| {
+        world.resource_mut::<InspectorToggle>().shown = shown;
+    });
+    commands.add(PrintStringCommand(format!(
+        "Inspector {}",
+        if shown { "shown" } else { "hidden" }
+    )));
+}
+
+fn setup_inspector_command(mut commands: Commands) {
+    commands.add(RegisterConsoleCommand::new(
+        "inspector".into(),
+        inspector_command,
+    ));
+}
+
+/// The world inspector, shown according to [`InspectorToggle`]
+pub struct Plugin;
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InspectorToggle>()
+            .add_plugins(WorldInspectorPlugin::new().run_if(inspector_shown))
+            .add_systems(Startup, setup_inspector_command)
+            .add_systems(Update, toggle_inspector_on_key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::ecs::system::CommandQueue;
+    use pirate_sim_console::{parse, Output};
+
+    use super::*;
+
+    #[test]
+    fn inspector_toggles() {
+        let mut toggle = InspectorToggle::default();
+        assert!(toggle.shown);
+
+        toggle.toggle();
+        assert!(!toggle.shown);
+        toggle.toggle();
+        assert!(toggle.shown);
+
+        // by key
+        let mut app = App::new();
+        app.init_resource::<InspectorToggle>()
+            .init_resource::<Input<KeyCode>>()
+            .add_systems(Update, toggle_inspector_on_key);
+
+        let key = app.world.resource::<InspectorToggle>().key;
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+        assert!(!app.world.resource::<InspectorToggle>().shown);
+
+        // by command
+        let mut world = World::new();
+        world.init_resource::<InspectorToggle>();
+        world.init_resource::<Events<Output>>();
+
+        let run = |world: &mut World, args: &[&str]| {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            inspector_command(
+                parse(&args.join(" ")).expect("arguments parse"),
+                &mut commands,
+            );
+            queue.apply(world);
+
+            world.resource::<InspectorToggle>().shown
+        };
+
+        assert!(!run(&mut world, &["off"]));
+        assert!(run(&mut world, &["on"]));
+        // anything else leaves it alone
+        assert!(run(&mut world, &["maybe"]));
+    }
+}
@@ -0,0 +1,496 @@
+//! Levels stored as assets, and systems for spawning them into the world
+//!
+//! A level file (`*.level`) is made of a legend followed by one or more layers:
+//!
+//! ```text
+//! legend
+//! w Wall wall 202 203 204
+//! f Floor floor 5 6 7
+//! layer
+//! www
+//! wfw
+//! www
+//! layer
+//! fff
+//! ```
+//!
+//! Each legend line is `<char> <name> <constraints> <main index> <one up index> <two up index>`,
+//! where constraints is one of `wall`, `floor`, `one_way`, `entity`, or `sensor`. A legend line of
+//! only `<char>` uses that char's [`TileDefinitions`] entry instead.
+//!
+//! Each layer is one z level, starting at 0. The first row of a layer is the highest y, and each
+//! column is one x. Spaces are left empty, and blank rows at the bottom of a layer are ignored.
+
+use std::{fmt::Display, str::FromStr};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::{BoxedFuture, HashMap, HashSet},
+};
+
+use pirate_sim_core::tile_grid::TileStretch;
+use pirate_sim_physics::{collision::Constraints, Collider};
+
+use pirate_sim_game::tile_objects::{SpriteSheetHandle, TileObject};
+
+/// Everything needed to spawn a single kind of tile
+#[derive(Debug, Clone)]
+pub struct TileDef {
+    pub name: String,
+    pub constraints: Constraints,
+    pub sprite: TileObject,
+}
+
+impl TileDef {
+    #[must_use]
+    pub fn new(name: impl Into<String>, constraints: Constraints, sprite: TileObject) -> Self {
+        Self {
+            name: name.into(),
+            constraints,
+            sprite,
+        }
+    }
+
+    /// Spawn this tile at `translation` as a child of `parent`
+    ///
+    /// Authored content like levels and ships is never counted against the
+    /// [`SpawnBudget`](pirate_sim_core::spawn_budget::SpawnBudget), so none of it can be dropped
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        translation: Vec3,
+        parent: Entity,
+        spritesheet_handle: &Handle<TextureAtlas>,
+    ) -> Entity {
+        commands
+            .spawn((
+                Collider::new(self.constraints),
+                self.sprite,
+                Name::new(self.name.clone()),
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite::new(self.sprite.main_layer_index),
+                    texture_atlas: spritesheet_handle.clone(),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+            ))
+            .set_parent(parent)
+            .id()
+    }
+}
+
+/// The kinds of tile that can be spawned by char, used by ship blueprints and by levels whose
+/// legend refers to a tile by only its char
+///
+/// Defaults to the tiles ships are built from, see the [ships module](crate::ships). Insert into
+/// this to add new tiles without touching any spawning code.
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct TileDefinitions(pub HashMap<char, TileDef>);
+
+impl Default for TileDefinitions {
+    fn default() -> Self {
+        let wall = TileDef::new(
+            "Ship Wall",
+            Constraints::WALL,
+            TileObject::new(202, 203, 204),
+        );
+        // TODO: give stairs, steering wheels, and cannons their own tiles. Until then they're at
+        // least something to stand on
+        let floor = TileDef::new("Ship Floor", Constraints::FLOOR, TileObject::new(5, 6, 7));
+
+        Self(
+            std::iter::once(('w', wall))
+                .chain(['f', '>', '<', 's', 'c', 'r'].map(|c| (c, floor.clone())))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, TypeUuid, TypePath)]
+#[uuid = "8b0d8f0c-6f5e-4f8c-9a55-0c4e4e8e2f31"]
+pub struct Level {
+    pub legend: HashMap<char, TileDef>,
+    /// chars the legend left to [`TileDefinitions`]
+    pub registered: HashSet<char>,
+    /// each layer is a list of rows, from the highest y to the lowest
+    pub layers: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLevelError {
+    /// a line was found outside of a `legend` or `layer` section
+    NoSection(usize),
+    /// a legend line had the wrong amount of fields, or an unparsable field
+    MalformedLegend(usize),
+    /// a legend line referenced unknown constraints
+    UnknownConstraints(usize),
+    /// a layer used a char not in the legend
+    UnknownTile(char),
+}
+
+impl Display for ParseLevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSection(line) => write!(f, "line {line} is not in a legend or layer section"),
+            Self::MalformedLegend(line) => write!(f, "malformed legend entry on line {line}"),
+            Self::UnknownConstraints(line) => write!(f, "unknown constraints on line {line}"),
+            Self::UnknownTile(c) => write!(f, "tile `{c}` is not in the legend"),
+        }
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
+
+/// Parse a legend line into its char, and its definition unless it's left to [`TileDefinitions`]
+fn parse_legend_line(
+    line_number: usize,
+    line: &str,
+) -> Result<(char, Option<TileDef>), ParseLevelError> {
+    let malformed = || ParseLevelError::MalformedLegend(line_number);
+    let single_char = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(malformed()),
+        }
+    };
+
+    let fields: Vec<_> = line.split_whitespace().collect();
+    let [tile, name, constraints, main, one_up, two_up] = fields[..] else {
+        return match fields[..] {
+            [tile] => Ok((single_char(tile)?, None)),
+            _ => Err(malformed()),
+        };
+    };
+
+    let tile = single_char(tile)?;
+
+    let constraints = Constraints::from_name(constraints)
+        .ok_or(ParseLevelError::UnknownConstraints(line_number))?;
+
+    let index = |s: &str| s.parse::<usize>().map_err(|_| malformed());
+
+    Ok((
+        tile,
+        Some(TileDef::new(
+            name,
+            constraints,
+            TileObject::new(index(main)?, index(one_up)?, index(two_up)?),
+        )),
+    ))
+}
+
+impl FromStr for Level {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        enum Section {
+            None,
+            Legend,
+            Layer,
+        }
+
+        let mut section = Section::None;
+        let mut legend = HashMap::new();
+        let mut registered = HashSet::new();
+        let mut layers: Vec<Vec<String>> = Vec::new();
+
+        for (line_number, line) in s.lines().enumerate() {
+            match line.trim_end() {
+                "legend" => section = Section::Legend,
+                "layer" => {
+                    section = Section::Layer;
+                    layers.push(Vec::new());
+                }
+                "" if !matches!(section, Section::Layer) => {}
+                line => match section {
+                    Section::None => return Err(ParseLevelError::NoSection(line_number)),
+                    Section::Legend => match parse_legend_line(line_number, line)? {
+                        (tile, Some(def)) => {
+                            legend.insert(tile, def);
+                        }
+                        (tile, None) => {
+                            registered.insert(tile);
+                        }
+                    },
+                    Section::Layer => {
+                        // a layer is pushed whenever we enter a layer section
+                        if let Some(layer) = layers.last_mut() {
+                            layer.push(line.to_owned());
+                        }
+                    }
+                },
+            }
+        }
+
+        // trailing blank rows would otherwise push the whole layer up
+        for layer in &mut layers {
+            while layer.last().is_some_and(|row| row.trim().is_empty()) {
+                layer.pop();
+            }
+        }
+
+        if let Some(unknown) = layers
+            .iter()
+            .flatten()
+            .flat_map(|row| row.chars())
+            .find(|c| *c != ' ' && !legend.contains_key(c) && !registered.contains(c))
+        {
+            return Err(ParseLevelError::UnknownTile(unknown));
+        }
+
+        Ok(Self {
+            legend,
+            registered,
+            layers,
+        })
+    }
+}
+
+impl Level {
+    /// Every non-empty tile in the level, along with its tile-space offset from the level's origin
+    ///
+    /// Chars the legend leaves to `definitions` are skipped if they aren't in it.
+    pub fn tiles<'a>(
+        &'a self,
+        definitions: &'a TileDefinitions,
+    ) -> impl Iterator<Item = (IVec3, &'a TileDef)> {
+        self.layers.iter().enumerate().flat_map(move |(z, layer)| {
+            layer.iter().rev().enumerate().flat_map(move |(y, row)| {
+                row.chars().enumerate().filter_map(move |(x, c)| {
+                    self.legend
+                        .get(&c)
+                        .or_else(|| definitions.get(&c).filter(|_| self.registered.contains(&c)))
+                        .map(|def| (IVec3::new(x as i32, y as i32, z as i32), def))
+                })
+            })
+        })
+    }
+
+    /// Spawn every tile in the level as a child of `parent`
+    ///
+    /// Returns the amount of tiles spawned
+    pub fn spawn_children(
+        &self,
+        parent: Entity,
+        commands: &mut Commands,
+        tile_stretch: TileStretch,
+        spritesheet_handle: &Handle<TextureAtlas>,
+        definitions: &TileDefinitions,
+    ) -> usize {
+        let mut spawned = 0;
+
+        for (offset, def) in self.tiles(definitions) {
+            def.spawn(
+                commands,
+                tile_stretch.get_bevy(offset),
+                parent,
+                spritesheet_handle,
+            );
+
+            spawned += 1;
+        }
+
+        spawned
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level: Level = std::str::from_utf8(bytes)?.parse()?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+/// Spawn a level as children of this entity once it has loaded. This component is removed once
+/// the level has been spawned.
+#[derive(Component, Debug)]
+pub struct SpawnLevel(pub Handle<Level>);
+
+pub fn spawn_level(
+    mut commands: Commands,
+    to_spawn_q: Query<(Entity, &SpawnLevel)>,
+    levels: Res<Assets<Level>>,
+    tile_stretch: Res<TileStretch>,
+    spritesheet_handle: Res<SpriteSheetHandle>,
+    tile_definitions: Res<TileDefinitions>,
+) {
+    for (entity, spawn_level) in &to_spawn_q {
+        // not loaded yet
+        let Some(level) = levels.get(&spawn_level.0) else {
+            continue;
+        };
+
+        let spawned = level.spawn_children(
+            entity,
+            &mut commands,
+            *tile_stretch,
+            &spritesheet_handle,
+            &tile_definitions,
+        );
+        debug!("spawned level with {spawned} tiles");
+
+        commands.entity(entity).remove::<SpawnLevel>();
+    }
+}
+
+pub struct Plugin;
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_resource::<TileDefinitions>()
+            .init_asset_loader::<LevelLoader>()
+            .add_systems(Update, spawn_level);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+
+    const TEST_LEVEL: &str = "legend
+w Wall wall 202 203 204
+f Floor floor 5 6 7
+layer
+www
+wfw
+www
+layer
+ f
+";
+
+    #[test]
+    fn level_parses_and_spawns() {
+        let level: Level = TEST_LEVEL.parse().expect("test level should parse");
+
+        assert_eq!(level.layers.len(), 2);
+        assert_eq!(level.tiles(&TileDefinitions::default()).count(), 10);
+
+        let mut world = World::new();
+        let parent = world.spawn(SpatialBundle::default()).id();
+
+        // a budget far smaller than the level doesn't drop any of it
+        world.insert_resource(pirate_sim_core::spawn_budget::SpawnBudget::new(1));
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let spawned = level.spawn_children(
+            parent,
+            &mut commands,
+            TileStretch::new(32, 32),
+            &Handle::default(),
+            &TileDefinitions::default(),
+        );
+        queue.apply(&mut world);
+
+        assert_eq!(spawned, 10);
+
+        let mut tile_q = world.query::<(&Name, &Transform, &Parent)>();
+        let tiles: Vec<_> = tile_q.iter(&world).collect();
+
+        assert_eq!(tiles.len(), 10);
+        assert!(tiles.iter().all(|(_, _, p)| p.get() == parent));
+        assert_eq!(
+            tiles.iter().filter(|(n, ..)| n.as_str() == "Wall").count(),
+            8
+        );
+
+        // the floors should be in the middle of the bottom layer, and the middle top of the
+        // second layer
+        let mut floors: Vec<_> = tiles
+            .iter()
+            .filter(|(n, ..)| n.as_str() == "Floor")
+            .map(|(_, t, _)| t.translation)
+            .collect();
+        floors.sort_by(|a, b| a.z.total_cmp(&b.z));
+
+        assert_eq!(
+            floors,
+            vec![Vec3::new(32., 32., 0.), Vec3::new(32., 0., 1.)]
+        );
+    }
+
+    #[test]
+    fn level_unknown_tile_fails() {
+        let result = "legend\nw Wall wall 1 2 3\nlayer\nwx\n".parse::<Level>();
+
+        assert_eq!(
+            result.expect_err("x is not in the legend"),
+            ParseLevelError::UnknownTile('x')
+        );
+    }
+
+    #[test]
+    fn level_uses_registered_tiles() {
+        let mut definitions = TileDefinitions::default();
+        definitions.insert(
+            'b',
+            TileDef::new("Barrel", Constraints::ENTITY, TileObject::new(40, 41, 42)),
+        );
+
+        // both are left to the definitions, which only have `b`
+        let level: Level = "legend\nb\nx\nlayer\nbx\n"
+            .parse()
+            .expect("test level should parse");
+
+        let mut world = World::new();
+        let parent = world.spawn(SpatialBundle::default()).id();
+
+        // a budget far smaller than the level doesn't drop any of it
+        world.insert_resource(pirate_sim_core::spawn_budget::SpawnBudget::new(1));
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let spawned = level.spawn_children(
+            parent,
+            &mut commands,
+            TileStretch::new(32, 32),
+            &Handle::default(),
+            &definitions,
+        );
+        queue.apply(&mut world);
+
+        assert_eq!(spawned, 1);
+
+        let (name, collider, sprite, atlas_sprite, transform) = world
+            .query::<(
+                &Name,
+                &Collider,
+                &TileObject,
+                &TextureAtlasSprite,
+                &Transform,
+            )>()
+            .single(&world);
+
+        assert_eq!(name.as_str(), "Barrel");
+        assert_eq!(collider.constraints, Constraints::ENTITY);
+        assert_eq!(
+            (
+                sprite.main_layer_index,
+                sprite.one_up_index,
+                sprite.two_up_index
+            ),
+            (40, 41, 42)
+        );
+        assert_eq!(atlas_sprite.index, 40);
+        assert_eq!(transform.translation, Vec3::ZERO);
+    }
+}
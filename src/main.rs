@@ -10,12 +10,16 @@
 #![allow(clippy::cast_possible_truncation)]
 
 use bevy::{app::AppExit, prelude::*, time::Stopwatch};
-use pirate_sim_controllers::{player::PlayerControllerBundle, WalkSpeed};
+use pirate_sim_controllers::{
+    player::{FollowPlayer, PlayerControllerBundle},
+    WalkSpeed,
+};
 
 use pirate_sim_core::tile_grid::TileStretch;
+use pirate_sim_game::tile_objects::{self, StretchFromAtlas};
 
 #[cfg(feature = "developer-tools")]
-use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
+use bevy_inspector_egui::bevy_egui::EguiPlugin;
 
 use pirate_sim_physics as physics;
 
@@ -27,8 +31,15 @@ use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 
 #[cfg(feature = "developer-tools")]
 mod basic_commands;
+#[cfg(feature = "developer-tools")]
+mod goal_readout;
+#[cfg(feature = "developer-tools")]
+mod inspector;
 
-mod tile_objects;
+mod level;
+#[cfg(feature = "developer-tools")]
+mod selection;
+mod ships;
 
 /// the bundle for spawning a player character
 #[derive(Bundle)]
@@ -115,15 +126,21 @@ fn main() {
     app.add_plugins(EguiPlugin);
 
     #[cfg(feature = "developer-tools")]
-    app.add_plugins(WorldInspectorPlugin::new());
+    app.add_plugins(inspector::Plugin);
 
     // our plugins
     app.add_plugins((
-        PhysicsPlugin,
+        PhysicsPlugin::default(),
         tile_objects::Plugin,
+        // the default sea level is just below the floors spawned in `setup`
+        pirate_sim_game::sea::Plugin::default(),
+        level::Plugin,
+        pirate_sim_core::timers::TimerPoolPlugin,
         pirate_sim_controllers::Plugin,
         #[cfg(feature = "developer-tools")]
         pirate_sim_console::Plugin,
+        #[cfg(feature = "developer-tools")]
+        pirate_sim_core::spawn_budget::SpawnBudgetPlugin { per_frame: 1000 },
     ));
 
     trace!("setting up resources, adding startup systems");
@@ -142,7 +159,13 @@ fn main() {
         .add_systems(Update, walk_perimeter);
 
     #[cfg(feature = "developer-tools")]
-    app.add_systems(Update, quit_on_eq);
+    app.add_systems(Update, quit_on_eq).add_systems(
+        Update,
+        (
+            goal_readout::update_goal_readouts.after(physics::PhysicsSet::Completed),
+            selection::pick_selected_entity,
+        ),
+    );
 
     trace!("Running app");
     app.run();
@@ -217,16 +240,20 @@ fn setup(
     mut sprites: ResMut<Assets<TextureAtlas>>,
 ) {
     // dwarfs (0,2)
-    // TODO: base on spritesheet
-    let tilestretch: TileStretch = TileStretch::new(32, 32);
-    commands.insert_resource(tilestretch);
-
     let texture_handle = asset_server.load("tilesets/main.png");
 
-    // TODO: image manipulation & get data for tilestretch
-
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, tilestretch.into(), 16, 16, None, None);
+    let texture_atlas = TextureAtlas::from_grid(
+        texture_handle,
+        tile_objects::SPRITE_SIZE,
+        tile_objects::SPRITESHEET_COLUMNS,
+        tile_objects::SPRITESHEET_ROWS,
+        None,
+        None,
+    );
+
+    let tilestretch =
+        TileStretch::from_atlas(&texture_atlas).expect("spritesheet should have uniform tiles");
+    commands.insert_resource(tilestretch);
 
     let texture_atlas_handle = sprites.add(texture_atlas);
 
@@ -242,6 +269,7 @@ fn setup(
             ..default()
         },
         TileCamera(),
+        FollowPlayer(),
     ));
 
     commands.spawn((
@@ -272,6 +300,7 @@ fn setup(
             pos_solid_planes: BVec3::new(false, false, true),
             neg_solid_planes: BVec3::FALSE,
             move_along: BVec3::FALSE,
+            one_way: false,
         }),
     ));
 
@@ -286,7 +315,7 @@ fn setup(
         },
         physics_component: pirate_sim_physics::PhysicsComponentBase::default(),
         weight: Weight(0.),
-        //TODO: figure out if 1. speed is really 1 grid per second
+        // tiles per second
         walkspeed: WalkSpeed(5.),
         collider: Collider::new(pirate_sim_physics::collision::Constraints::ENTITY),
         name: Name::new("Player"),
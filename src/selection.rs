@@ -0,0 +1,49 @@
+//! Picking entities with the mouse, so that console commands can refer to them as `@selected`
+//!
+//! See [`pirate_sim_console::SelectedEntity`]
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use pirate_sim_console::SelectedEntity;
+use pirate_sim_core::tile_grid::{GetTileLocation, TileStretch};
+
+use pirate_sim_game::tile_objects::TileCamera;
+
+/// Select the topmost named entity on the tile under the cursor when the left mouse button is
+/// clicked. Clicking an empty tile clears the selection.
+pub fn pick_selected_entity(
+    mouse: Res<Input<MouseButton>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<TileCamera>>,
+    named_q: Query<(Entity, &GlobalTransform), With<Name>>,
+    tile_stretch: Res<TileStretch>,
+    mut selected: ResMut<SelectedEntity>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(cursor) = window_q.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+
+    // screen -> world -> tile
+    let Some(cursor_tile) = camera_q.iter().find_map(|(camera, camera_transform)| {
+        camera
+            .viewport_to_world_2d(camera_transform, cursor)
+            .map(|world| tile_stretch.get_closest(world.extend(0.)))
+    }) else {
+        return;
+    };
+
+    let picked = named_q
+        .iter()
+        .map(|(entity, transform)| (entity, transform.location(*tile_stretch)))
+        .filter(|(_, location)| location.truncate() == cursor_tile.truncate())
+        .max_by_key(|(_, location)| location.z)
+        .map(|(entity, _)| entity);
+
+    debug!("selected {picked:?} at {cursor_tile}");
+
+    selected.0 = picked;
+}
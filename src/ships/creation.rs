@@ -1,19 +1,13 @@
-use std::{cell::RefCell, rc::Rc};
+use bevy::{ecs::system::SystemState, prelude::*};
 
-use crate::{
-    physics,
+use pirate_sim_core::{
     random::Generator,
-    ships::BASIC_SHIP,
-    tile_grid::TileStretch,
-    tile_objects::{self, SpriteSheetHandle},
-};
-use bevy::{
-    ecs::system::{CommandQueue, SystemState},
-    prelude::*,
-    transform::commands,
+    tile_grid::{checked_tile_add, checked_tile_mul, TileStretch},
 };
+use pirate_sim_game::tile_objects::SpriteSheetHandle;
+use pirate_sim_physics::{self as physics, fluid::SeaLevel};
 
-use super::SeaLevel;
+use crate::{level::TileDefinitions, ships::BASIC_SHIP};
 
 // TODO: multiply these by the ship size or something
 const FIRST_SHIP_RANGE: i32 = 200;
@@ -27,112 +21,208 @@ fn setup_ships(world: &mut World) {
         Res<TileStretch>,
         Res<SpriteSheetHandle>,
         Res<SeaLevel>,
+        Res<TileDefinitions>,
         Commands,
     )>::new(world);
 
-    let (generator, tile_stretch, spritesheet_handle, sea_level, mut commands) =
+    let (generator, tile_stretch, spritesheet_handle, sea_level, tile_definitions, mut commands) =
         system.get_mut(world);
 
     let mut g = generator;
     let first_ship_translate_tile_space = IVec3::new(
         g.range(-FIRST_SHIP_RANGE, FIRST_SHIP_RANGE),
         g.range(-FIRST_SHIP_RANGE, FIRST_SHIP_RANGE),
-        sea_level.0,
+        sea_level.z(),
     );
 
     // if statement maps {true,false} => {-1,1} to get ship 2 below or to the left as well
-    let x_offset = g.range(SECOND_SHIP_OFFSET_MIN, SECOND_SHIP_OFFSET_MAX)
-        * if g.rand::<bool>() { -1 } else { 1 };
-    let y_offset = g.range(SECOND_SHIP_OFFSET_MIN, SECOND_SHIP_OFFSET_MAX)
-        * if g.rand::<bool>() { -1 } else { 1 };
-
-    let second_ship_translate_tile_space = IVec3::new(
-        x_offset + first_ship_translate_tile_space.x,
-        y_offset + first_ship_translate_tile_space.y,
-        1,
-    );
-
-    // spawn first ship
-    spawn_ship_from_blueprint(
-        &first_ship_translate_tile_space,
-        (0, 0),
-        &BASIC_SHIP,
-        &mut commands,
-        &tile_stretch,
-        &spritesheet_handle,
-    );
+    let x_distance = g.range(SECOND_SHIP_OFFSET_MIN, SECOND_SHIP_OFFSET_MAX);
+    let x_sign = if g.rand::<bool>() { -1 } else { 1 };
+    let y_distance = g.range(SECOND_SHIP_OFFSET_MIN, SECOND_SHIP_OFFSET_MAX);
+    let y_sign = if g.rand::<bool>() { -1 } else { 1 };
+
+    // the ranges are small enough now, but won't be once they're multiplied by ship sizes
+    let second_ship_translate_tile_space = checked_tile_mul(
+        IVec3::new(x_distance, y_distance, 0),
+        IVec3::new(x_sign, y_sign, 0),
+    )
+    .and_then(|offset| {
+        checked_tile_add(offset, first_ship_translate_tile_space.truncate().extend(1))
+    });
+
+    if second_ship_translate_tile_space.is_none() {
+        warn!("second ship would be placed out of bounds, only spawning the first");
+    }
 
-    todo!();
+    // spawn both ships
+    for translation in
+        std::iter::once(first_ship_translate_tile_space).chain(second_ship_translate_tile_space)
+    {
+        spawn_ship_from_blueprint(
+            translation,
+            &BASIC_SHIP,
+            &mut commands,
+            *tile_stretch,
+            &spritesheet_handle,
+            &tile_definitions,
+        );
+    }
 
     system.apply(world); // make it so our changes actually take effect
 }
 
-fn spawn_ship_from_blueprint(
-    start_translation: &IVec3,
-    dimensions: (u8, u8),
+/// Spawn a ship with its origin at `start_translation` (in tilespace), returning the ship's root
+/// entity. Every tile of the ship is spawned as a child of the root.
+///
+/// Each element of `blueprint` is one z level, starting at the ship's origin. The first non-blank
+/// row of a level is the highest y, and each column is one x. See the [module docs](super) for
+/// the meaning of each char, which is looked up in `tile_definitions`.
+pub(super) fn spawn_ship_from_blueprint(
+    start_translation: IVec3,
     blueprint: &[&str],
     commands: &mut Commands,
-    tile_stretch: &TileStretch,
+    tile_stretch: TileStretch,
     spritesheet_handle: &Handle<TextureAtlas>,
-) {
+    tile_definitions: &TileDefinitions,
+) -> Entity {
     let ship = commands
         .spawn((
-            Transform::from_translation(tile_stretch.get_bevy(*start_translation)),
+            SpatialBundle::from_transform(Transform::from_translation(
+                tile_stretch.get_bevy(start_translation),
+            )),
             physics::PhysicsComponentBase::default(),
+            Name::new("Ship"),
         ))
         .id();
 
-    // todo convert this to Iter::enumerate
-
-    // having the loop the way that it is makes it clear how and why we're getting and using x,y,z
-    #[allow(clippy::needless_range_loop)]
-    for z in 0..blueprint.len() {
-        for x in 0..dimensions.0 as usize {
-            for y in 0..dimensions.1 as usize {
-                // index into blueprint
-                let char = blueprint[z]
-                    .chars()
-                    .nth(x * dimensions.0 as usize + y)
-                    .expect("Malformed blueprint or incorrect dimensions - attempted to index char that does not exist");
-
-                let current_translation = IVec3::new(x as i32, y as i32, z as i32);
-
-                match char {
-                    ' ' => {} // ignore spaces
-                    'w' => spawn_wall(
-                        commands,
-                        tile_stretch.get_bevy(current_translation),
-                        ship,
-                        spritesheet_handle,
-                    ),
-                    c => {
+    for (z, layer) in blueprint.iter().enumerate() {
+        // skip the leading newline of each layer, and go from the bottom up
+        let rows = layer.lines().filter(|row| !row.trim().is_empty()).rev();
+
+        for (y, row) in rows.enumerate() {
+            for (x, char) in row.chars().enumerate() {
+                let location = tile_stretch.get_bevy(IVec3::new(x as i32, y as i32, z as i32));
+
+                match (char, tile_definitions.get(&char)) {
+                    (' ', _) => {} // ignore spaces
+                    (_, Some(def)) => {
+                        def.spawn(commands, location, ship, spritesheet_handle);
+                    }
+                    (c, None) => {
                         panic!("blueprint char {c} not recognized")
                     }
                 }
             }
         }
     }
+
+    ship
 }
 
-fn spawn_wall(
-    commands: &mut Commands,
-    location: Vec3,
-    parent: Entity,
-    spritesheet_handle: &Handle<TextureAtlas>,
-) {
-    commands
-        .spawn((
-            physics::collider::Collider::new(physics::collider::Constraints::WALL),
-            physics::velocity::VelocityBundle::default(),
-            tile_objects::TileObject::new(202, 203, 204),
-            Name::new("Ship Wall"),
-            SpriteSheetBundle {
-                // TODO: dynamically update walls or something
-                sprite: TextureAtlasSprite::new(202),
-                texture_atlas: spritesheet_handle.clone(),
-                transform: Transform::from_translation(location),
-                ..default()
-            },
-        ))
-        .set_parent(parent);
+#[cfg(feature = "developer-tools")]
+pub(crate) mod console {
+    use bevy::{
+        ecs::system::{Command, CommandQueue},
+        prelude::*,
+    };
+    use pirate_sim_console::{Output, PrintStringCommand, Token};
+    use pirate_sim_core::tile_grid::TileStretch;
+    use pirate_sim_game::tile_objects::SpriteSheetHandle;
+    use std::{collections::VecDeque, str::FromStr};
+
+    use crate::{level::TileDefinitions, ships::BASIC_SHIP};
+
+    pub fn spawnship_console(input: VecDeque<Token>, commands: &mut Commands) {
+        // spawnship <x> <y> <z>
+
+        if input.len() != 3 {
+            commands.add(PrintStringCommand(format!(
+                "Wrong amount of inputs. Expected 3, got {}",
+                input.len()
+            )));
+            return;
+        }
+
+        let parsed = input
+            .iter()
+            .map(|t| t.string.parse::<i32>())
+            .collect::<Result<Vec<_>, <i32 as FromStr>::Err>>();
+
+        match parsed {
+            Ok(at) => commands.add(SpawnShipCommand {
+                at: IVec3::new(at[0], at[1], at[2]),
+            }),
+            Err(e) => commands.add(PrintStringCommand(format!("Parsing error `{e}`"))),
+        }
+    }
+
+    /// Spawn a [`BASIC_SHIP`] with its origin at `at`
+    pub(crate) struct SpawnShipCommand {
+        pub at: IVec3,
+    }
+
+    impl Command for SpawnShipCommand {
+        fn apply(self, world: &mut World) {
+            let tile_stretch = *world.resource::<TileStretch>();
+            let spritesheet_handle = world.resource::<SpriteSheetHandle>().0.clone();
+
+            let ship = world.resource_scope(|world, tile_definitions: Mut<TileDefinitions>| {
+                let mut queue = CommandQueue::default();
+                let mut commands = Commands::new(&mut queue, world);
+
+                let ship = super::spawn_ship_from_blueprint(
+                    self.at,
+                    &BASIC_SHIP,
+                    &mut commands,
+                    tile_stretch,
+                    &spritesheet_handle,
+                    &tile_definitions,
+                );
+
+                queue.apply(world);
+                ship
+            });
+
+            world.send_event(Output::String(format!("Spawned ship {ship:?}")));
+            world.send_event(Output::End);
+        }
+    }
+
+    #[cfg(test)]
+    #[test]
+    fn spawnship_spawns_blueprint() {
+        use pirate_sim_console::parse;
+
+        let mut world = World::new();
+
+        world.insert_resource(TileStretch::new(32, 32));
+        world.insert_resource(SpriteSheetHandle(Handle::default()));
+        world.init_resource::<TileDefinitions>();
+        world.init_resource::<Events<Output>>();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        spawnship_console(parse("1 2 3").expect("arguments parse"), &mut commands);
+        queue.apply(&mut world);
+
+        let (ship, ship_transform) = world
+            .query_filtered::<(Entity, &Transform), With<Children>>()
+            .single(&world);
+
+        assert_eq!(ship_transform.translation, Vec3::new(32., 64., 3.));
+
+        let mut tile_q = world.query::<(&Name, &Transform, &Parent)>();
+        let tiles: Vec<_> = tile_q
+            .iter(&world)
+            .filter(|(.., p)| p.get() == ship)
+            .map(|(n, t, _)| (n.as_str(), t.translation))
+            .collect();
+
+        // the bottom left corner of the bottom layer is a wall, and just inside of it is floor
+        assert!(tiles.contains(&("Ship Wall", Vec3::new(64., 0., 0.))));
+        assert!(tiles.contains(&("Ship Floor", Vec3::new(128., 32., 0.))));
+        // the top layer is all floor
+        assert!(tiles.contains(&("Ship Floor", Vec3::new(64., 0., 2.))));
+        assert!(!tiles.contains(&("Ship Wall", Vec3::new(64., 0., 2.))));
+    }
 }
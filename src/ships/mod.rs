@@ -11,20 +11,16 @@
 //! 'c' = canon
 //!
 //! ' ' = open space
+//!
+//! Every char but ' ' is spawned from its [`TileDefinitions`](crate::level::TileDefinitions)
+//! entry, so new kinds of tile can be added there
 
 // still under heavy development
 #![allow(unused)]
 
 use bevy::prelude::*;
 
-use crate::{
-    physics::{self, collider::Collider},
-    random::Generator,
-    tile_grid::TileStretch,
-    tile_objects,
-};
-
-mod creation;
+pub(crate) mod creation;
 mod interaction;
 
 /// a basic template for a ship. not piratey at all because I suck at art
@@ -61,10 +57,6 @@ const BASIC_SHIP: [&str; 3] = [
 
 pub struct ShipBundle;
 
-/// this doesn't belong here. the sea level of the world
-#[derive(Debug, Resource, Deref)]
-pub struct SeaLevel(i32);
-
 #[derive(Bundle)]
 pub struct SteeringWheelBundle {
     main_component: SteeringWheel,